@@ -0,0 +1,73 @@
+//! Time converting a large export: `cargo bench --bench transform`.
+//!
+//! To compare a change with the code before it, save a baseline first
+//! with `cargo bench --bench transform -- --save-baseline before`, then
+//! run `cargo bench --bench transform -- --baseline before` on the
+//! change.
+
+use criterion::{criterion_group, criterion_main, Criterion, Throughput};
+use wordpress_to_zola::convert_str;
+
+const POSTS: usize = 200;
+
+/// Content the way WordPress exports it: paragraphs separated by blank
+/// lines, with some markup, a footnote and a shortcode mixed in.
+const CONTENT: &str = r##"<h2 id="intro">Introduction</h2>
+Some text with <b>bold</b> and <a href="https://example.com/a">a link</a>.
+
+Another paragraph<sup><a href="#fn1" id="ref1">1</a></sup> and <a href="#intro">back up</a>.
+
+<ul>
+<li>one</li>
+<li>two</li>
+</ul>
+
+[audio mp3="https://example.com/a.mp3"][/audio]
+
+<ol class="footnotes"><li id="fn1">The note. <a href="#ref1">↩</a></li></ol>"##;
+
+fn export() -> String {
+    let mut xml = String::from(
+        r#"<?xml version="1.0" encoding="UTF-8" ?>
+        <rss version="2.0"
+            xmlns:content="http://purl.org/rss/1.0/modules/content/"
+            xmlns:wp="http://wordpress.org/export/1.2/"
+        >
+        <channel>
+            <title>Blog</title>
+            <wp:base_site_url>https://example.com</wp:base_site_url>"#,
+    );
+    for i in 0..POSTS {
+        xml.push_str(&format!(
+            r#"<item>
+                <title>Post {i}</title>
+                <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                <link>https://example.com/blog/post{i}</link>
+                <content:encoded><![CDATA[{content}]]></content:encoded>
+                <wp:post_type><![CDATA[post]]></wp:post_type>
+                <wp:status><![CDATA[publish]]></wp:status>
+            </item>"#,
+            i = i,
+            content = CONTENT,
+        ));
+    }
+    xml.push_str("</channel></rss>");
+    xml
+}
+
+fn convert(c: &mut Criterion) {
+    let xml = export();
+    let mut group = c.benchmark_group("convert");
+    group.sample_size(20);
+    group.throughput(Throughput::Elements(POSTS as u64));
+    group.bench_function("posts", |b| {
+        b.iter(|| {
+            let files = convert_str(&xml).unwrap();
+            assert!(files.len() > POSTS);
+        })
+    });
+    group.finish();
+}
+
+criterion_group!(benches, convert);
+criterion_main!(benches);
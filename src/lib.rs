@@ -0,0 +1,6945 @@
+//! # wordpress-to-zola
+//! Wordress to Zola converter.
+//!
+//! ## What & Why?
+//!
+//! This is a small tool for generating sections and pages for
+//! [zola][] from wordress XML.  If you want to move your blog from
+//! wordress to zola, this tool will do that for you.
+//!
+//! ## How do I use it?
+//!
+//! First you should go to your wordpress's `/wp-admin/export.php` and
+//! download XML file.  Then you run `cargo run -- input.xml` and it
+//! will produce a `content` directory will all the pages and
+//! sections.
+//!
+//! ## How does it work?
+//!
+//! TODO: document
+//!
+//! ## Debugging
+//! One may want to set logging level to debug to see more details.
+//! ```sh
+//! export RUST_LOG=wordpress_to_zola=debug
+//! cargo run
+//! ```
+//!
+//! [zola][https://www.getzola.org/]
+
+mod markdown;
+mod tag_handlers;
+mod transform_html;
+mod zip_fs;
+
+use chrono::{DateTime, Duration, FixedOffset, NaiveDateTime, SecondsFormat, Utc};
+use log::*;
+use markdown::{links, plain_spaces, shortcodes, strip_shortcodes, wrap};
+use regex::{Captures, Regex};
+use serde::Deserialize;
+use serde_xml_rs::from_reader;
+use std::borrow::Cow;
+use std::cell::RefCell;
+use std::collections::hash_map::DefaultHasher;
+use std::collections::{BTreeMap, HashMap, HashSet};
+use std::fs::create_dir_all;
+use std::fs::File;
+use std::hash::{Hash, Hasher};
+use std::io::{Error, ErrorKind, Read, Result, Write};
+use std::path::{Path, PathBuf};
+use std::str::FromStr;
+use std::sync::LazyLock;
+use tag_handlers::html_to_markdown;
+use transform_html::transform_html;
+
+pub use zip_fs::ZipFs;
+
+/// Paginate sections by this number of posts unless `--paginate` says
+/// otherwise.
+const PAGINATE_BY: usize = 5;
+
+/// Footer added by `--append-source-note`. `{date}` and `{url}` are
+/// replaced with the post's publication date and original link.
+pub const DEFAULT_SOURCE_NOTE: &str = "> Originally published on {date} at {url}.";
+
+/// Conversion settings.
+#[derive(Debug, Clone)]
+pub struct Options {
+    /// Section for posts we can't derive a section for from their link.
+    pub default_section: String,
+    /// Nest sections at most this deep, joining deeper parts of links
+    /// into the slug: `2019/03/12/post` is `2019/03-12-post` at 1.
+    pub max_depth: Option<usize>,
+    /// Number posts in each section newest first and sort sections by
+    /// `weight`, so the order can be tuned by hand later.
+    pub weights: bool,
+    /// Replace post dates with future ones this many days apart, oldest
+    /// post first starting tomorrow, to publish gradually.
+    pub stagger: Option<u32>,
+    /// Remove shortcodes left over after conversion.
+    pub strip_shortcodes: bool,
+    /// Write posts as page bundles: `post/index.md` instead of `post.md`.
+    pub bundles: bool,
+    /// Download attachments and point links to the local copies.
+    pub download_media: bool,
+    /// Write post content as is, for blogs written in markdown, which
+    /// html2md would escape. Zola renders html in it as well.
+    pub passthrough_markdown: bool,
+    /// Write the html fed to html2md next to every page, to review the
+    /// conversion.
+    pub emit_raw_html: bool,
+    /// Generate `config.toml` next to the output directory.
+    pub config: bool,
+    /// Keep old WordPress urls working with `aliases`.
+    pub aliases: bool,
+    /// Leave out aliases that are the url of the page anyway.
+    pub only_changed_aliases: bool,
+    /// Taxonomy of `<category>` elements without a domain.
+    pub bare_categories: BareCategories,
+    /// Names of the shortcodes we write.
+    pub shortcodes: Shortcodes,
+    /// What to do with `<!--nextpage-->` markers, which zola ignores.
+    pub next_page: Option<NextPage>,
+    /// Timezone of naive wordpress dates, used when `pubDate` is broken.
+    pub assume_tz: Option<FixedOffset>,
+    /// Item field the `date` of pages comes from.
+    pub date_field: DateField,
+    /// Whether aliases and manifest paths end with `/`.
+    pub trailing_slash: TrailingSlash,
+    /// Convert only the post with this slug, for debugging.
+    pub only: Option<String>,
+    /// Stop after converting this many posts.
+    pub max_posts: Option<usize>,
+    /// Hard-wrap paragraphs at this many columns.
+    pub wrap: Option<usize>,
+    /// Write every outbound link to `external-links.txt` for link checking.
+    pub external_links: bool,
+    /// Template of a note appended to every post, see
+    /// [`DEFAULT_SOURCE_NOTE`].
+    pub source_note: Option<String>,
+    /// Directory with hand written `_index.md` files named after their
+    /// section, e.g. `blog.md`, used instead of the generated ones.
+    pub section_template_dir: Option<PathBuf>,
+    /// Fail on the first item that cannot be converted instead of
+    /// skipping it.
+    pub strict: bool,
+    /// Normalize the case of post titles.
+    pub title_case: Option<TitleCase>,
+    /// Title posts without one after their slug, or `Untitled`.
+    pub title_from_slug: bool,
+    /// Convert posts with these statuses.
+    pub statuses: Vec<Status>,
+    /// Keep links with `rel` or `target` attributes as html.
+    pub keep_link_attributes: bool,
+    /// Convert password protected posts instead of skipping them.
+    pub protected: Option<Protected>,
+    /// Extension of generated pages and section indexes.
+    pub extension: String,
+    /// Write section `_index.md` files, off when they are managed by hand.
+    pub sections: bool,
+    /// Make sections below the root transparent, so their pages show on
+    /// the homepage. Some themes list such pages twice.
+    pub transparent_sections: bool,
+    /// Write `<taxonomy>/<term>/_index.md` for categories and tags with
+    /// a description.
+    pub term_pages: bool,
+    /// Put categories and tags into zola taxonomies.
+    pub taxonomies: bool,
+    /// Keep categories and tags as `original_categories` and
+    /// `original_tags` in `[extra]`, whether or not they are taxonomies.
+    pub original_terms: bool,
+    /// Convert posts without content instead of skipping them.
+    pub keep_empty: bool,
+    /// Turn non-breaking spaces from `&nbsp;` into regular ones.
+    pub plain_spaces: bool,
+    /// Put posts into sections by date instead of by their links.
+    pub group_by: Option<GroupBy>,
+    /// Put drafts into a `drafts` section of their own.
+    pub drafts: bool,
+    /// Write `manifest.json` mapping old urls to the new pages.
+    pub manifest: bool,
+    /// Write `posts.csv` listing the converted pages.
+    pub csv_index: bool,
+    /// How to write `<dl>` definition lists, which markdown lacks.
+    pub definition_lists: DefinitionList,
+    /// Toml file with fields added to the front matter of every page,
+    /// see [`FrontMatter`].
+    pub front_matter_template: Option<PathBuf>,
+    /// Directory under the output directory for all pages and sections,
+    /// to merge the blog into an existing site.
+    pub base_path: Option<PathBuf>,
+    /// Urls of the blogs of a multisite export like
+    /// `https://example.com/travel`, each converted into a directory of
+    /// its own named after it.
+    pub blogs: Vec<String>,
+    /// Elements kept as html, like `details` or `kbd`, which markdown
+    /// can't express.
+    pub keep_tags: Vec<String>,
+    /// `wp-content/uploads` of a WordPress backup to copy linked files
+    /// from instead of downloading them.
+    pub uploads_dir: Option<PathBuf>,
+    /// Keep text colors as html `<span>`s instead of dropping them.
+    pub keep_colors: bool,
+    /// Skip posts whose markdown matches any of these, like generated
+    /// link roundups.
+    pub exclude_content: Vec<Regex>,
+    /// Write `<audio>` and `<video>` as `audio` and `video` shortcodes
+    /// instead of html.
+    pub media_shortcodes: bool,
+    /// Posts per page by section, like `blog/news`, with `""` for the
+    /// home page and 0 turning pagination off. Term pages go by
+    /// `categories/rust`, or by `categories` for all of them.
+    pub paginate: BTreeMap<String, usize>,
+}
+
+impl Default for Options {
+    fn default() -> Self {
+        Self {
+            default_section: "posts".to_owned(),
+            max_depth: None,
+            weights: false,
+            stagger: None,
+            strip_shortcodes: false,
+            bundles: false,
+            download_media: false,
+            passthrough_markdown: false,
+            emit_raw_html: false,
+            config: false,
+            aliases: false,
+            only_changed_aliases: false,
+            bare_categories: BareCategories::Tags,
+            shortcodes: Shortcodes::default(),
+            next_page: None,
+            assume_tz: None,
+            date_field: DateField::PubDate,
+            trailing_slash: TrailingSlash::Keep,
+            only: None,
+            max_posts: None,
+            wrap: None,
+            external_links: false,
+            source_note: None,
+            section_template_dir: None,
+            strict: false,
+            title_case: None,
+            title_from_slug: false,
+            statuses: vec![Status::Publish],
+            keep_link_attributes: false,
+            protected: None,
+            extension: "md".to_owned(),
+            sections: true,
+            transparent_sections: true,
+            term_pages: false,
+            taxonomies: true,
+            original_terms: false,
+            keep_empty: false,
+            plain_spaces: false,
+            group_by: None,
+            drafts: false,
+            manifest: false,
+            csv_index: false,
+            definition_lists: DefinitionList::Colon,
+            front_matter_template: None,
+            base_path: None,
+            blogs: Vec::new(),
+            keep_tags: Vec::new(),
+            uploads_dir: None,
+            keep_colors: false,
+            exclude_content: Vec::new(),
+            media_shortcodes: false,
+            paginate: BTreeMap::new(),
+        }
+    }
+}
+
+/// How to normalize post titles.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TitleCase {
+    /// `all lower case`
+    Lower,
+    /// `Every Word Capitalized`
+    Title,
+    /// `Only the first word capitalized`
+    Sentence,
+}
+
+impl FromStr for TitleCase {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name {
+            "lower" => Ok(TitleCase::Lower),
+            "title" => Ok(TitleCase::Title),
+            "sentence" => Ok(TitleCase::Sentence),
+            _ => Err(format!(
+                "Unknown title case {}, expected lower, title or sentence",
+                name
+            )),
+        }
+    }
+}
+
+/// Sections to put posts into instead of the ones from their links.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GroupBy {
+    /// `2019/post.md`
+    Year,
+}
+
+impl FromStr for GroupBy {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name {
+            "year" => Ok(GroupBy::Year),
+            _ => Err(format!("Unknown grouping {}, expected year", name)),
+        }
+    }
+}
+
+/// How to write the terms and definitions of `<dl>` lists.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DefinitionList {
+    /// `**Term**` and `: Definition` on the next line
+    Colon,
+    /// `**Term** — Definition` on one line
+    Dash,
+}
+
+impl FromStr for DefinitionList {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name {
+            "colon" => Ok(DefinitionList::Colon),
+            "dash" => Ok(DefinitionList::Dash),
+            _ => Err(format!(
+                "Unknown definition list style {}, expected colon or dash",
+                name
+            )),
+        }
+    }
+}
+
+/// What `<category>` elements without a domain are, as in old exports
+/// and plain rss feeds.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BareCategories {
+    Tags,
+    Categories,
+    Ignore,
+}
+
+impl BareCategories {
+    fn taxonomy(self) -> Option<&'static str> {
+        match self {
+            BareCategories::Tags => Some("tags"),
+            BareCategories::Categories => Some("categories"),
+            BareCategories::Ignore => None,
+        }
+    }
+}
+
+impl FromStr for BareCategories {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name {
+            "tags" => Ok(BareCategories::Tags),
+            "categories" => Ok(BareCategories::Categories),
+            "ignore" => Ok(BareCategories::Ignore),
+            _ => Err(format!(
+                "Unknown taxonomy {} for bare categories, expected tags, categories or ignore",
+                name
+            )),
+        }
+    }
+}
+
+/// How to treat posts which WordPress shows on several pages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NextPage {
+    /// One page without the markers.
+    Strip,
+    /// A bundle with `index.md` for the first page, `2.md` for the
+    /// second and so on, which zola serves at `post/2/` like WordPress.
+    Split,
+}
+
+impl FromStr for NextPage {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name {
+            "strip" => Ok(NextPage::Strip),
+            "split" => Ok(NextPage::Split),
+            _ => Err(format!(
+                "Unknown nextpage mode {}, expected strip or split",
+                name
+            )),
+        }
+    }
+}
+
+/// Names of the shortcodes we write, so they can match a theme's.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Shortcodes {
+    pub youtube: String,
+    pub vimeo: String,
+    pub pullquote: String,
+}
+
+impl Default for Shortcodes {
+    /// Zola's built-in `youtube` and `vimeo`, and the usual `pullquote`.
+    fn default() -> Self {
+        Self {
+            youtube: "youtube".to_owned(),
+            vimeo: "vimeo".to_owned(),
+            pullquote: "pullquote".to_owned(),
+        }
+    }
+}
+
+/// Whether generated url paths end with a slash.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrailingSlash {
+    /// `/blog/post/`, like wordpress permalinks and zola urls
+    Keep,
+    /// `/blog/post`
+    Strip,
+}
+
+impl TrailingSlash {
+    fn apply(self, path: &str) -> String {
+        match self {
+            TrailingSlash::Keep => path.to_owned(),
+            TrailingSlash::Strip => {
+                let end = path.find(['?', '#']).unwrap_or(path.len());
+                let stripped = path[..end].trim_end_matches('/');
+                let stripped = if stripped.is_empty() { "/" } else { stripped };
+                format!("{}{}", stripped, &path[end..])
+            }
+        }
+    }
+}
+
+impl FromStr for TrailingSlash {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name {
+            "keep" => Ok(TrailingSlash::Keep),
+            "strip" => Ok(TrailingSlash::Strip),
+            _ => Err(format!(
+                "Unknown trailing slash policy {}, expected keep or strip",
+                name
+            )),
+        }
+    }
+}
+
+/// WordPress field holding the date of a post.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum DateField {
+    /// `pubDate`, with the offset of the blog
+    PubDate,
+    /// `wp:post_date`, local time taken to be in `assume_tz`, or UTC
+    PostDate,
+    /// `wp:post_date_gmt`
+    PostDateGmt,
+}
+
+impl DateField {
+    /// Date of `item` from this field, `None` for `pubDate` which is
+    /// always parsed as the fallback.
+    fn date(self, item: &Item, timezone: Option<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+        match self {
+            DateField::PubDate => None,
+            DateField::PostDate => {
+                let timezone = timezone.unwrap_or(FixedOffset::east_opt(0).expect("zero offset"));
+                parse_wordpress_date(item.post_date.as_deref())?
+                    .and_local_timezone(timezone)
+                    .single()
+            }
+            DateField::PostDateGmt => parse_wordpress_date(item.post_date_gmt.as_deref())
+                .map(|date| date.and_utc().fixed_offset()),
+        }
+    }
+}
+
+impl FromStr for DateField {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name {
+            "pubDate" => Ok(DateField::PubDate),
+            "post_date" => Ok(DateField::PostDate),
+            "post_date_gmt" => Ok(DateField::PostDateGmt),
+            _ => Err(format!(
+                "Unknown date field {}, expected pubDate, post_date or post_date_gmt",
+                name
+            )),
+        }
+    }
+}
+
+/// What to do with the body of password protected posts.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Protected {
+    /// Write only the front matter.
+    Omit,
+    /// Write the body as is.
+    Keep,
+}
+
+impl FromStr for Protected {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        match name {
+            "omit" => Ok(Protected::Omit),
+            "keep" => Ok(Protected::Keep),
+            _ => Err(format!(
+                "Unknown protected mode {}, expected omit or keep",
+                name
+            )),
+        }
+    }
+}
+
+/// What happened during a conversion.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Summary {
+    /// Posts written, counting a post split into pages once.
+    pub converted: usize,
+    /// Items left out because they lack what's needed to convert them.
+    pub skipped: usize,
+    /// Items which failed to convert.
+    pub errors: usize,
+    /// Things worth a look in the converted items.
+    pub warnings: Vec<Warning>,
+    /// Uses of shortcodes nothing converted, by name.
+    pub shortcodes: BTreeMap<String, usize>,
+    /// Posts of every term by taxonomy.
+    pub terms: BTreeMap<String, BTreeMap<String, usize>>,
+}
+
+impl Summary {
+    /// Whether every item made it through.
+    pub fn is_clean(&self) -> bool {
+        self.skipped == 0 && self.errors == 0
+    }
+
+    /// Count a failed item, or give up in strict mode.
+    fn error(&mut self, options: &Options, message: String) -> Result<()> {
+        if options.strict {
+            return Err(Error::new(ErrorKind::InvalidData, message));
+        }
+        warn!("{}", message);
+        self.errors += 1;
+        Ok(())
+    }
+
+    /// Unconverted shortcodes, most used first: `contact-form (12),
+    /// rev_slider (3)`.
+    pub fn shortcode_tally(&self) -> Option<String> {
+        if self.shortcodes.is_empty() {
+            return None;
+        }
+        let mut tally: Vec<(&String, &usize)> = self.shortcodes.iter().collect();
+        tally.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+        let tally: Vec<String> = tally
+            .iter()
+            .map(|(name, count)| format!("{} ({})", name, count))
+            .collect();
+        Some(tally.join(", "))
+    }
+
+    /// Terms of every taxonomy with their number of posts, most used
+    /// first, to spot typos and near duplicates.
+    pub fn term_report(&self) -> String {
+        let mut report = String::new();
+        for (taxonomy, terms) in &self.terms {
+            report.push_str(&format!("{}:\n", taxonomy));
+            let mut terms: Vec<(&String, &usize)> = terms.iter().collect();
+            terms.sort_by(|a, b| b.1.cmp(a.1).then(a.0.cmp(b.0)));
+            for (term, count) in terms {
+                report.push_str(&format!("    {}: {}\n", term, count));
+            }
+        }
+        report
+    }
+
+    /// Log a warning and keep it for the caller.
+    fn warn(&mut self, warning: Warning) {
+        warn!("{}", warning);
+        self.warnings.push(warning);
+    }
+}
+
+/// Something worth a look in a converted item, with the item's link.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Warning {
+    /// `pubDate` is broken, `post_date_gmt` or `post_date` was used
+    /// instead.
+    DateFallback { link: String },
+    /// The item has neither link nor post name and was skipped.
+    NoPath { title: String },
+    /// An attachment has no usable url or failed to download.
+    UnresolvedMedia { link: String },
+    /// A shortcode was removed from the post.
+    DroppedShortcode { link: String, name: String },
+    /// The post has no content.
+    EmptyContent { link: String },
+    /// The item lacks an element needed to tell what it is and was
+    /// skipped.
+    MissingField { title: String, field: &'static str },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Warning::DateFallback { link } => {
+                write!(
+                    f,
+                    "Cannot parse pubDate of {}, using the wordpress date",
+                    link
+                )
+            }
+            Warning::NoPath { title } => {
+                write!(f, "Skipping {}: it has neither link nor post name", title)
+            }
+            Warning::UnresolvedMedia { link } => write!(f, "Cannot localize attachment {}", link),
+            Warning::DroppedShortcode { link, name } => {
+                write!(f, "Removed shortcode [{}] from {}", name, link)
+            }
+            Warning::EmptyContent { link } => write!(f, "Post {} has no content", link),
+            Warning::MissingField { title, field } => {
+                write!(f, "Skipping item '{}': it has no {}", title, field)
+            }
+        }
+    }
+}
+
+/// A post to be written as a zola page.
+#[derive(Debug, Clone)]
+pub struct Page {
+    pub title: String,
+    pub date: DateTime<FixedOffset>,
+    /// Only built by `zola serve --drafts`.
+    pub draft: bool,
+    pub weight: Option<usize>,
+    /// Old urls redirecting to the page.
+    pub aliases: Vec<String>,
+    /// Terms of the page by taxonomy: `categories = ["C++"]`.
+    pub taxonomies: Vec<(String, Vec<String>)>,
+    /// Other top level fields like `template`, values are toml.
+    pub fields: Vec<(String, String)>,
+    /// Fields of the `[extra]` table for themes, values are toml. Kept
+    /// sorted by key so regenerated pages diff cleanly.
+    pub extra: BTreeMap<String, String>,
+    pub markdown: String,
+}
+
+/// Front matter fields shared by all pages, read from a toml snippet
+/// with top level keys and an `[extra]` table.
+#[derive(Debug, Default)]
+pub struct FrontMatter {
+    pub fields: Vec<(String, String)>,
+    pub extra: Vec<(String, String)>,
+}
+
+impl FrontMatter {
+    /// Parse one `key = value` per line, values are kept as toml.
+    pub fn parse(toml: &str) -> Result<Self> {
+        let mut front_matter = FrontMatter::default();
+        let mut in_extra = false;
+        for line in toml.lines().map(str::trim) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if line.starts_with('[') {
+                in_extra = match line {
+                    "[extra]" => true,
+                    _ => {
+                        return Err(Error::new(
+                            ErrorKind::InvalidData,
+                            format!("Unsupported table {} in front matter, only [extra]", line),
+                        ))
+                    }
+                };
+                continue;
+            }
+            let (key, value) = line.split_once('=').ok_or_else(|| {
+                Error::new(
+                    ErrorKind::InvalidData,
+                    format!("Expected key = value in front matter, got {}", line),
+                )
+            })?;
+            let field = (key.trim().to_owned(), value.trim().to_owned());
+            if in_extra {
+                front_matter.extra.push(field);
+            } else {
+                front_matter.fields.push(field);
+            }
+        }
+        Ok(front_matter)
+    }
+
+    /// Add the fields to `page`, keeping the ones computed for it.
+    fn apply(&self, page: &mut Page) {
+        for (key, value) in &self.fields {
+            let computed = match key.as_str() {
+                "title" | "date" => true,
+                "draft" => page.draft,
+                "weight" => page.weight.is_some(),
+                "aliases" => !page.aliases.is_empty(),
+                _ => page.fields.iter().any(|(field, _)| field == key),
+            };
+            if !computed {
+                page.fields.push((key.clone(), value.clone()));
+            }
+        }
+        for (key, value) in &self.extra {
+            page.extra
+                .entry(key.clone())
+                .or_insert_with(|| value.clone());
+        }
+    }
+}
+
+/// Site settings for `config.toml`.
+#[derive(Debug)]
+pub struct Config {
+    pub base_url: String,
+    pub title: String,
+    /// Tagline of the blog.
+    pub description: Option<String>,
+    /// When the blog was last built according to the export.
+    pub last_build_date: Option<DateTime<FixedOffset>>,
+    /// Export file the site was converted from.
+    pub source: String,
+    pub generated_at: DateTime<Utc>,
+    /// Display names of the terms of every taxonomy used, by their
+    /// WordPress nicename.
+    pub taxonomies: BTreeMap<String, BTreeMap<String, String>>,
+}
+
+/// Where an old WordPress url went.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ManifestEntry {
+    pub old_url: String,
+    /// Url path of the page in zola: `/blog/post/`.
+    pub path: String,
+    pub slug: String,
+}
+
+/// A converted page in `posts.csv`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct IndexEntry {
+    pub title: String,
+    pub date: DateTime<FixedOffset>,
+    pub slug: String,
+    /// Url path of the page in zola: `/blog/post/`.
+    pub path: String,
+    pub authors: Vec<String>,
+    pub tags: Vec<String>,
+}
+
+/// Front matter of a section `_index.md` file.
+#[derive(Debug)]
+pub struct Section {
+    pub title: Option<String>,
+    pub description: Option<String>,
+    /// Pass pages up to the parent section, so they show on the homepage.
+    pub transparent: bool,
+    pub sort_by: &'static str,
+    pub paginate_by: usize,
+    /// Contents used verbatim instead of the generated front matter.
+    pub template: Option<String>,
+    pub markdown: String,
+}
+
+/// Read xml from `input_file` and create `zola` content directory in
+/// `output_dir`.
+pub fn convert(
+    input_file: PathBuf,
+    output_dir: PathBuf,
+    options: &Options,
+    fs: &impl Fs,
+) -> Result<Summary> {
+    convert_with(input_file, output_dir, options, fs, str::to_owned)
+}
+
+/// Like [`convert`], but runs `post_process` on the markdown of every
+/// post right before writing it, for site specific cleanups.
+pub fn convert_with(
+    input_file: PathBuf,
+    output_dir: PathBuf,
+    options: &Options,
+    fs: &impl Fs,
+    post_process: impl Fn(&str) -> String,
+) -> Result<Summary> {
+    convert_all_with(
+        std::slice::from_ref(&input_file),
+        output_dir,
+        options,
+        fs,
+        post_process,
+    )
+}
+
+/// Like [`convert_with`], but merges the posts and terms of several
+/// exports, like one split by wordpress into parts. The blog's title and
+/// urls are taken from the first one.
+pub fn convert_all_with(
+    input_files: &[PathBuf],
+    output_dir: PathBuf,
+    options: &Options,
+    fs: &impl Fs,
+    post_process: impl Fn(&str) -> String,
+) -> Result<Summary> {
+    let (input_file, rest) = input_files
+        .split_first()
+        .ok_or_else(|| Error::new(ErrorKind::InvalidInput, "No input files"))?;
+    let mut rss = read_rss(input_file, fs)?;
+    for part in rest {
+        rss.channel.merge(read_rss(part, fs)?.channel);
+    }
+    let front_matter = match &options.front_matter_template {
+        Some(path) => match fs.read_file(path)? {
+            Some(toml) => FrontMatter::parse(&toml)?,
+            None => {
+                return Err(Error::new(
+                    ErrorKind::NotFound,
+                    format!("Front matter template {} not found", path.display()),
+                ))
+            }
+        },
+        None => FrontMatter::default(),
+    };
+    // the blog's home page, sections above it belong to the site
+    let blog_dir = match &options.base_path {
+        Some(base_path) => output_dir.join(base_path),
+        None => output_dir.clone(),
+    };
+    let mut summary = Summary::default();
+    rss.channel.check_version();
+    let last_build_date = rss.channel.build_date();
+    let site_title = rss.channel.title.clone();
+    let site_description = rss
+        .channel
+        .description
+        .as_deref()
+        .map(str::trim)
+        .filter(|description| !description.is_empty())
+        .map(str::to_owned);
+
+    // We want to strip `base_url` from posts url later on to get a
+    // nice filename for a post.
+    let base_url = rss.channel.base_url();
+    let channel_terms = rss.channel.terms();
+    let items = rss.channel.item;
+    if items.is_empty() {
+        info!("No items found in {}", input_file.display());
+    }
+
+    // Pages are collected first, as weights and media links depend on
+    // all posts.
+    let mut posts = Vec::new();
+    let mut attachments = Vec::new();
+    // pages written so far, to tell apart items sharing a link
+    let mut taken: HashSet<PathBuf> = HashSet::new();
+    // broken plugins give many items the same link, like `?p=0`
+    let mut seen_links = HashSet::new();
+    let shared_links: HashSet<String> = items
+        .iter()
+        .filter_map(|item| item.link.as_deref())
+        .filter(|link| !seen_links.insert(*link))
+        .map(str::to_owned)
+        .collect();
+    // items converted so far, a split post counts once
+    let mut converted_items = 0;
+    // terms defined for the blog are known even if no post uses them
+    let mut terms: BTreeMap<String, BTreeMap<String, String>> = BTreeMap::new();
+    for term in &channel_terms {
+        terms
+            .entry(term.taxonomy.to_owned())
+            .or_default()
+            .insert(term.nicename.clone(), term.name.clone());
+    }
+
+    for item in items {
+        if let Some(field) = item.missing_field() {
+            summary.warn(Warning::MissingField {
+                title: item.title.clone(),
+                field,
+            });
+            summary.skipped += 1;
+            continue;
+        }
+        match item.post_type {
+            PostType::Post => {
+                if !options.statuses.contains(&item.status) {
+                    debug!("Skipping {} post {}", item.status, item.title);
+                    continue;
+                }
+                if item.is_protected() && options.protected.is_none() {
+                    info!("Skipping password protected post {}", item.title);
+                    continue;
+                }
+                if let Some(slug) = &options.only {
+                    if !item.has_slug(slug) {
+                        debug!("Skipping {}: only converting {}", item.title, slug);
+                        continue;
+                    }
+                }
+                if options.max_posts.is_some_and(|max| converted_items >= max) {
+                    debug!(
+                        "Skipping {}: already converted {} posts",
+                        item.title, converted_items
+                    );
+                    continue;
+                }
+                let link = item.link.clone().unwrap_or_default();
+                let date = match options.date_field.date(&item, options.assume_tz) {
+                    Some(date) => Ok(date),
+                    None => DateTime::parse_from_rfc2822(&item.pub_date),
+                };
+                let date = match date {
+                    Ok(date) => date,
+                    Err(error) => match item.wordpress_date(options.assume_tz) {
+                        Some(date) => {
+                            summary.warn(Warning::DateFallback { link: link.clone() });
+                            date
+                        }
+                        None => {
+                            let message =
+                                format!("Cannot parse pubDate of {}: {}", item.title, error);
+                            summary.error(options, message)?;
+                            continue;
+                        }
+                    },
+                };
+
+                let (site_url, site_dir) = match blog_of(&options.blogs, &link) {
+                    Some(blog) => (blog, blog_dir.join(blog_name(blog))),
+                    None => (base_url.as_str(), blog_dir.clone()),
+                };
+                let path = match item_path(site_url, &item, options) {
+                    Some(path) => site_dir.join(group_path(path, &item.status, &date, options)),
+                    None => {
+                        summary.warn(Warning::NoPath {
+                            title: item.title.clone(),
+                        });
+                        summary.skipped += 1;
+                        continue;
+                    }
+                };
+                let shared = shared_links.contains(&link) || link.contains('?');
+                let path = match item
+                    .fallback_slug()
+                    .filter(|_| shared || taken.contains(&path))
+                {
+                    Some(slug) => path.with_file_name(file_name(&slug, &options.extension)),
+                    None => path,
+                };
+                let path = unique_path(path, &taken, &options.extension);
+                taken.insert(path.clone());
+                let section = path.parent().expect("no parent in filename").to_owned();
+                let path = if options.bundles {
+                    path.with_extension("")
+                        .join("index")
+                        .with_extension(&options.extension)
+                } else {
+                    path
+                };
+                info!("Post [{:?}] {} -> {:?}", item.status, item.title, &path);
+
+                let omitted = item.is_protected() && options.protected == Some(Protected::Omit);
+                let content = if omitted { "" } else { item.content() };
+                let parts = next_pages(content, options.next_page);
+                let (html, mut markdown) = if options.passthrough_markdown {
+                    let markdown = parts.join(PAGE_BREAK);
+                    (markdown.clone(), markdown.replace("\r\n", "\n"))
+                } else {
+                    let mut html = Vec::new();
+                    let mut markdown = Vec::new();
+                    for part in parts {
+                        let part = transform_html(&part, options);
+                        markdown.push(html_to_markdown(&part, options));
+                        html.push(part);
+                    }
+                    (html.join(PAGE_BREAK), markdown.join(PAGE_BREAK))
+                };
+                // blank paragraphs and `&nbsp;` leave only whitespace
+                if !omitted && markdown.trim().is_empty() {
+                    summary.warn(Warning::EmptyContent { link: link.clone() });
+                    if !options.keep_empty {
+                        summary.skipped += 1;
+                        continue;
+                    }
+                    markdown.clear();
+                }
+                let excluded = options
+                    .exclude_content
+                    .iter()
+                    .find(|pattern| pattern.is_match(&markdown));
+                if let Some(pattern) = excluded {
+                    info!("Skipping {}: its content matches {}", item.title, pattern);
+                    continue;
+                }
+                for name in shortcodes(&markdown) {
+                    *summary.shortcodes.entry(name).or_default() += 1;
+                }
+                if options.strip_shortcodes {
+                    let (stripped, removed) = strip_shortcodes(&markdown);
+                    for name in removed {
+                        summary.warn(Warning::DroppedShortcode {
+                            link: link.clone(),
+                            name,
+                        });
+                    }
+                    markdown = stripped;
+                }
+                if options.plain_spaces {
+                    markdown = plain_spaces(&markdown);
+                }
+                if let Some(width) = options.wrap {
+                    markdown = wrap(&markdown, width);
+                }
+                if let Some(note) = &options.source_note {
+                    let note = note
+                        .replace("{date}", &date.format("%Y-%m-%d").to_string())
+                        .replace("{url}", &link);
+                    if !markdown.is_empty() {
+                        markdown.push_str("\n\n");
+                    }
+                    markdown.push_str(&note);
+                }
+
+                let aliases = if options.aliases {
+                    let page_url = url_parts(&path, &output_dir).join("/");
+                    item.old_urls()
+                        .iter()
+                        .map(|url| url_path(url))
+                        .filter(|old| {
+                            !options.only_changed_aliases || old.trim_matches('/') != page_url
+                        })
+                        .map(|old| options.trailing_slash.apply(&old))
+                        .collect()
+                } else {
+                    Vec::new()
+                };
+
+                let title = match item.title.trim() {
+                    "" if options.title_from_slug => item.slug_title(),
+                    _ => item.title.clone(),
+                };
+                let title = match options.title_case {
+                    Some(case) => change_case(&title, case),
+                    None => title,
+                };
+                let mut extra = BTreeMap::new();
+                if item.status == Status::Private {
+                    // themes can hide these behind a login
+                    extra.insert("visibility".to_owned(), "\"private\"".to_owned());
+                }
+                if item.is_protected() {
+                    extra.insert("protected".to_owned(), "true".to_owned());
+                }
+                let authors: Vec<String> = item
+                    .authors()
+                    .iter()
+                    .map(|author| author.to_string())
+                    .collect();
+                let quoted: Vec<String> = authors.iter().map(|author| quote(author)).collect();
+                match quoted.as_slice() {
+                    [] => {}
+                    [author] => {
+                        extra.insert("author".to_owned(), author.clone());
+                    }
+                    _ => {
+                        extra.insert("authors".to_owned(), format!("[{}]", quoted.join(", ")));
+                    }
+                }
+
+                // zola slugifies display names itself, so they are the
+                // terms, and nicenames are only kept for the config
+                let mut taxonomies: Vec<(String, Vec<String>)> = Vec::new();
+                for category in &item.category {
+                    let taxonomy = match category.taxonomy(options.bare_categories) {
+                        Some(taxonomy) => taxonomy,
+                        None => continue,
+                    };
+                    terms
+                        .entry(taxonomy.to_owned())
+                        .or_default()
+                        .insert(category.nicename(), category.name.clone());
+                    match taxonomies.iter_mut().find(|(name, _)| name == taxonomy) {
+                        Some((_, names)) if names.contains(&category.name) => {}
+                        Some((_, names)) => names.push(category.name.clone()),
+                        None => taxonomies.push((taxonomy.to_owned(), vec![category.name.clone()])),
+                    }
+                }
+                if options.original_terms {
+                    for (taxonomy, names) in &taxonomies {
+                        let names: Vec<String> = names.iter().map(|name| quote(name)).collect();
+                        extra.insert(
+                            format!("original_{}", taxonomy),
+                            format!("[{}]", names.join(", ")),
+                        );
+                    }
+                }
+                for (taxonomy, names) in &taxonomies {
+                    let counts = summary.terms.entry(taxonomy.clone()).or_default();
+                    for name in names {
+                        *counts.entry(name.clone()).or_default() += 1;
+                    }
+                }
+                if !options.taxonomies {
+                    taxonomies.clear();
+                }
+
+                let mut pages: Vec<String> = match options.next_page {
+                    Some(NextPage::Split) => NEXT_PAGE
+                        .split(&markdown)
+                        .map(|page| page.trim().to_owned())
+                        .collect(),
+                    _ => vec![markdown],
+                };
+                let markdown = pages.remove(0);
+                // later pages go next to the first one, which needs a bundle
+                let path = if !pages.is_empty() && !options.bundles {
+                    path.with_extension("")
+                        .join("index")
+                        .with_extension(&options.extension)
+                } else {
+                    path
+                };
+
+                let page = Page {
+                    title,
+                    date,
+                    draft: item.status.is_draft(),
+                    weight: None,
+                    aliases,
+                    taxonomies,
+                    fields: Vec::new(),
+                    extra,
+                    markdown,
+                };
+                let bundle = path.parent().expect("no parent in filename").to_owned();
+                for (n, markdown) in (2..).zip(pages) {
+                    posts.push(Post {
+                        id: None,
+                        link: None,
+                        section: section.clone(),
+                        path: bundle.join(format!("{}.{}", n, options.extension)),
+                        page: Page {
+                            title: format!("{} ({})", page.title, n),
+                            aliases: Vec::new(),
+                            markdown,
+                            ..page.clone()
+                        },
+                        authors: authors.clone(),
+                        raw_html: None,
+                    });
+                }
+                posts.push(Post {
+                    id: item.post_id,
+                    link: item.link,
+                    section,
+                    path,
+                    page,
+                    authors,
+                    raw_html: options.emit_raw_html.then_some(html),
+                });
+                converted_items += 1;
+            }
+            PostType::Attachment if options.download_media => attachments.push(item),
+            PostType::Attachment => debug!("Ignoring attachment {}", item.title),
+            PostType::Other(name) => {
+                debug!("Ignoring unknown post type '{}': {}", name, item.title)
+            }
+        }
+    }
+
+    if let Some(days) = options.stagger {
+        let tomorrow = Utc::now().date_naive() + Duration::days(1);
+        let start = tomorrow.and_time(Default::default()).and_utc();
+        stagger_dates(&mut posts, days, start.fixed_offset());
+    }
+    if options.weights {
+        assign_weights(&mut posts);
+    }
+
+    // sections named after a category are titled with its display name
+    let category_names = terms.get("categories").cloned().unwrap_or_default();
+
+    // Zola site root with `config.toml` and `static` is the parent of
+    // the content directory.
+    let site_dir = output_dir.parent().unwrap_or(&output_dir);
+
+    if options.config {
+        let config = Config {
+            base_url: base_url.clone(),
+            title: site_title.clone(),
+            description: site_description.clone(),
+            last_build_date,
+            source: input_file
+                .file_name()
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_default(),
+            generated_at: Utc::now(),
+            taxonomies: if options.taxonomies {
+                terms
+            } else {
+                BTreeMap::new()
+            },
+        };
+        fs.create_config(&site_dir.join("config.toml"), &config)?;
+    }
+
+    let mut media = Media::new(site_dir.join("static"));
+    for attachment in &attachments {
+        localize_attachment(
+            attachment,
+            &mut posts,
+            &base_url,
+            &mut media,
+            options,
+            fs,
+            &mut summary,
+        )?;
+    }
+
+    if let Some(uploads_dir) = &options.uploads_dir {
+        for post in &mut posts {
+            copy_uploads(post, uploads_dir, &mut media, options, fs, &mut summary)?;
+        }
+    }
+
+    if options.external_links {
+        let mut seen = HashSet::new();
+        let external: Vec<String> = posts
+            .iter()
+            .flat_map(|post| links(&post.page.markdown))
+            .filter(|link| !is_internal(&base_url, link))
+            .filter(|link| seen.insert(link.clone()))
+            .collect();
+        fs.create_links(&site_dir.join("external-links.txt"), &external)?;
+    }
+
+    if options.manifest {
+        let entries: Vec<ManifestEntry> = posts
+            .iter()
+            .filter_map(|post| {
+                let old_url = post.link.clone().filter(|link| !link.trim().is_empty())?;
+                let parts = url_parts(&post.path, &output_dir);
+                Some(ManifestEntry {
+                    old_url,
+                    path: options
+                        .trailing_slash
+                        .apply(&format!("/{}/", parts.join("/"))),
+                    slug: parts.last().cloned().unwrap_or_default(),
+                })
+            })
+            .collect();
+        fs.create_manifest(&site_dir.join("manifest.json"), &entries)?;
+    }
+
+    if options.csv_index {
+        let entries: Vec<IndexEntry> = posts
+            .iter()
+            .map(|post| {
+                let parts = url_parts(&post.path, &output_dir);
+                IndexEntry {
+                    title: post.page.title.clone(),
+                    date: post.page.date,
+                    slug: parts.last().cloned().unwrap_or_default(),
+                    path: options
+                        .trailing_slash
+                        .apply(&format!("/{}/", parts.join("/"))),
+                    authors: post.authors.clone(),
+                    tags: post
+                        .page
+                        .taxonomies
+                        .iter()
+                        .find(|(taxonomy, _)| taxonomy == "tags")
+                        .map(|(_, tags)| tags.clone())
+                        .unwrap_or_default(),
+                }
+            })
+            .collect();
+        fs.create_index(&site_dir.join("posts.csv"), &entries)?;
+    }
+
+    if options.term_pages && options.taxonomies {
+        for term in &channel_terms {
+            let description = match term.description.as_deref() {
+                Some(description) if !description.trim().is_empty() => description,
+                _ => continue,
+            };
+            // the term is the display name, see `Category`
+            let slug = slugify(&term.name);
+            let dir = output_dir.join(term.taxonomy).join(&slug);
+            fs.create_dir_all(&dir)?;
+            let paginate_by = options
+                .paginate
+                .get(&format!("{}/{}", term.taxonomy, slug))
+                .or_else(|| options.paginate.get(term.taxonomy));
+            let section = Section {
+                title: Some(term.name.clone()),
+                description: None,
+                transparent: false,
+                sort_by: "date",
+                paginate_by: paginate_by.copied().unwrap_or(PAGINATE_BY),
+                template: None,
+                markdown: html_to_markdown(&transform_html(description, options), options),
+            };
+            let index = dir.join("_index").with_extension(&options.extension);
+            fs.create_section(&index, &section)?;
+        }
+    }
+
+    // We will make `_index.md` for every section we will find and the
+    // sections above it up to the homepage. This set is used to only do
+    // that once per section.
+    let mut sections = HashSet::new();
+
+    for post in &mut posts {
+        // ensure all directories are in place
+        let dir = post.path.parent().expect("no parent in filename");
+        debug!("Creating directory {:?}", dir);
+        fs.create_dir_all(dir)?;
+
+        // zola needs a section file in every directory down to the post
+        let section_dirs: Vec<&Path> = if options.sections {
+            post.section
+                .ancestors()
+                .take_while(|dir| dir.starts_with(&blog_dir))
+                .collect()
+        } else {
+            Vec::new()
+        };
+        for section_dir in section_dirs.into_iter().rev() {
+            // if it's the first time we see this section, create section file
+            if !sections.insert(section_dir.to_owned()) {
+                continue;
+            }
+            let mut section = if section_dir == blog_dir {
+                Section {
+                    title: Some(site_title.clone()),
+                    description: site_description.clone(),
+                    transparent: false,
+                    sort_by: "date",
+                    paginate_by: PAGINATE_BY,
+                    template: None,
+                    markdown: String::new(),
+                }
+            } else {
+                let slug = section_dir
+                    .file_name()
+                    .map(|name| name.to_string_lossy())
+                    .unwrap_or_default();
+                Section {
+                    title: Some(section_title(&slug, &category_names)),
+                    description: None,
+                    transparent: options.transparent_sections,
+                    sort_by: if options.weights { "weight" } else { "date" },
+                    paginate_by: PAGINATE_BY,
+                    template: None,
+                    markdown: String::new(),
+                }
+            };
+            let name = section_dir.strip_prefix(&blog_dir).unwrap_or(section_dir);
+            let key: Vec<String> = name
+                .iter()
+                .map(|part| part.to_string_lossy().into_owned())
+                .collect();
+            if let Some(&paginate_by) = options.paginate.get(&key.join("/")) {
+                section.paginate_by = paginate_by;
+            }
+            if let Some(template_dir) = &options.section_template_dir {
+                section.template = fs.read_file(&section_template_path(template_dir, name))?;
+            }
+            let index = section_dir
+                .join("_index")
+                .with_extension(&options.extension);
+            fs.create_section(&index, &section)?;
+        }
+
+        post.page.markdown = post_process(&post.page.markdown);
+        front_matter.apply(&mut post.page);
+        fs.create_page(&post.path, &post.page)?;
+        if let Some(html) = &post.raw_html {
+            fs.create_html(&post.path.with_extension("html"), html)?;
+        }
+    }
+    summary.converted = converted_items;
+    if let Some(tally) = summary.shortcode_tally() {
+        warn!("Unhandled shortcodes: {}", tally);
+    }
+    Ok(summary)
+}
+
+/// Converted post waiting to be written.
+struct Post {
+    /// WordPress id, attachments refer to their post with it.
+    id: Option<String>,
+    /// WordPress permalink.
+    link: Option<String>,
+    /// Directory of the section the post belongs to.
+    section: PathBuf,
+    path: PathBuf,
+    page: Page,
+    authors: Vec<String>,
+    /// Transformed html the markdown was made from, to write beside it.
+    raw_html: Option<String>,
+}
+
+/// Segments of the url path of the page at `path`: `blog/post.md` and
+/// `blog/post/index.md` are both `blog`, `post`.
+fn url_parts(path: &Path, output_dir: &Path) -> Vec<String> {
+    let page = path.strip_prefix(output_dir).unwrap_or(path);
+    let page = if page.file_stem().is_some_and(|stem| stem == "index") {
+        page.parent().unwrap_or(page).to_owned()
+    } else {
+        page.with_extension("")
+    };
+    page.iter()
+        .map(|part| part.to_string_lossy().into_owned())
+        .collect()
+}
+
+/// Number pages within every section from the newest to the oldest.
+/// Ties are broken by path so weights are stable across runs.
+fn assign_weights(posts: &mut [Post]) {
+    let mut order: Vec<usize> = (0..posts.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&posts[a], &posts[b]);
+        a.section
+            .cmp(&b.section)
+            .then(b.page.date.cmp(&a.page.date))
+            .then(a.path.cmp(&b.path))
+    });
+
+    let mut weights: HashMap<PathBuf, usize> = HashMap::new();
+    for i in order {
+        let post = &mut posts[i];
+        let weight = weights.entry(post.section.clone()).or_insert(0);
+        *weight += 1;
+        post.page.weight = Some(*weight);
+    }
+}
+
+/// Marker WordPress splits a post into pages at.
+static NEXT_PAGE: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\n*<!--\s*nextpage\s*-->\n*").unwrap());
+
+/// What `NEXT_PAGE` markers become in converted content.
+const PAGE_BREAK: &str = "\n\n<!--nextpage-->\n\n";
+
+/// Content of each page of a post to convert on its own: the whole
+/// post unless it is to be split.
+fn next_pages(content: &str, next_page: Option<NextPage>) -> Vec<Cow<'_, str>> {
+    match next_page {
+        None => vec![Cow::Borrowed(content)],
+        Some(NextPage::Strip) => vec![NEXT_PAGE.replace_all(content, "\n\n")],
+        Some(NextPage::Split) => NEXT_PAGE.split(content).map(Cow::Borrowed).collect(),
+    }
+}
+
+/// Date posts `days` apart from `start` on, keeping their order. Paths
+/// and notes already made from the original dates stay as they are.
+fn stagger_dates(posts: &mut [Post], days: u32, start: DateTime<FixedOffset>) {
+    let mut order: Vec<usize> = (0..posts.len()).collect();
+    order.sort_by(|&a, &b| {
+        let (a, b) = (&posts[a], &posts[b]);
+        a.page.date.cmp(&b.page.date).then(a.path.cmp(&b.path))
+    });
+    for (n, i) in order.into_iter().enumerate() {
+        posts[i].page.date = start + Duration::days(i64::from(days) * n as i64);
+    }
+}
+
+/// Download an attachment into the bundle of the post it belongs to,
+/// or into `static/uploads` if there is no such bundle, and point
+/// links to the local copy.
+fn localize_attachment(
+    attachment: &Item,
+    posts: &mut [Post],
+    base_url: &str,
+    media: &mut Media,
+    options: &Options,
+    fs: &impl Fs,
+    summary: &mut Summary,
+) -> Result<()> {
+    let url = match &attachment.attachment_url {
+        Some(url) => url,
+        None => {
+            summary.warn(Warning::UnresolvedMedia {
+                link: attachment.link.clone().unwrap_or_default(),
+            });
+            summary.skipped += 1;
+            return Ok(());
+        }
+    };
+    let file_name = match url.rsplit('/').next().filter(|name| !name.is_empty()) {
+        Some(name) => name,
+        None => {
+            summary.warn(Warning::UnresolvedMedia { link: url.clone() });
+            summary.skipped += 1;
+            return Ok(());
+        }
+    };
+
+    let parent = posts
+        .iter()
+        .position(|post| post.id.is_some() && post.id == attachment.post_parent)
+        .filter(|_| options.bundles);
+    let contents = match fs.fetch(url) {
+        Ok(contents) => contents,
+        Err(error) => {
+            summary
+                .warnings
+                .push(Warning::UnresolvedMedia { link: url.clone() });
+            return summary.error(options, format!("Cannot download {}: {}", url, error));
+        }
+    };
+    let bundle = parent.map(|i| posts[i].path.parent().expect("no parent in filename"));
+    let link = media.store(bundle, file_name, &contents, fs)?;
+
+    // posts link scaled variants like `cat-300x200.jpg` as well, and
+    // attachments of the site by root-relative urls too
+    let origin = match is_internal(base_url, url) {
+        true => url.strip_suffix(&url_path(url)),
+        false => None,
+    };
+    let (stem, extension) = match url
+        .rfind('.')
+        .filter(|&dot| dot > url.rfind('/').unwrap_or(0))
+    {
+        Some(dot) => url.split_at(dot),
+        None => (url.as_str(), ""),
+    };
+    let stem = match origin {
+        Some(origin) => format!(
+            "(?:{})?{}",
+            regex::escape(origin),
+            regex::escape(&stem[origin.len()..])
+        ),
+        None => regex::escape(stem),
+    };
+    let variants = Regex::new(&format!(
+        r"{}(?:-\d+x\d+)?{}",
+        stem,
+        regex::escape(extension)
+    ))
+    .expect("escaped url is a valid regex");
+    // the caption describes the image better than the alt text
+    // written when inserting it, if any
+    let images = attachment.excerpt().map(|caption| {
+        let caption = caption.split_whitespace().collect::<Vec<_>>().join(" ");
+        let markdown = Regex::new(&format!(
+            r#"!\[(?:\\.|[^\]\\])*\]\(({})(\s+"(?:\\.|[^"\\])*")?\)"#,
+            variants.as_str()
+        ))
+        .expect("escaped url is a valid regex");
+        // sized images stay html
+        let html = Regex::new(&format!(
+            r#"<img\s[^>]*\bsrc="(?:{})"[^>]*>"#,
+            variants.as_str()
+        ))
+        .expect("escaped url is a valid regex");
+        (markdown, html, caption)
+    });
+    let posts = match parent {
+        Some(i) => &mut posts[i..=i],
+        None => posts,
+    };
+    for post in posts.iter_mut() {
+        if let Some((markdown, html, caption)) = &images {
+            let alt = caption.replace('[', "\\[").replace(']', "\\]");
+            post.page.markdown = markdown
+                .replace_all(&post.page.markdown, |image: &Captures| {
+                    let title = image.get(2).map_or("", |title| title.as_str());
+                    format!("![{}]({}{})", alt, &image[1], title)
+                })
+                .into_owned();
+            post.page.markdown = html
+                .replace_all(&post.page.markdown, |tag: &Captures| {
+                    with_alt(&tag[0], caption)
+                })
+                .into_owned();
+        }
+        post.page.markdown = replace_urls(&post.page.markdown, &variants, &link);
+    }
+    Ok(())
+}
+
+/// `<img>` `tag` with `alt` as its alt text.
+fn with_alt(tag: &str, alt: &str) -> String {
+    static ALT: LazyLock<Regex> = LazyLock::new(|| Regex::new(r#"\salt="[^"]*""#).unwrap());
+
+    let alt = format!(
+        " alt=\"{}\"",
+        alt.replace('&', "&amp;").replace('"', "&quot;")
+    );
+    match ALT.find(tag) {
+        Some(old) => format!("{}{}{}", &tag[..old.start()], alt, &tag[old.end()..]),
+        None => tag.replacen("<img", &format!("<img{}", alt), 1),
+    }
+}
+
+/// Media files written so far by directory and contents, so that
+/// identical files are stored once and different ones sharing a name
+/// are kept apart.
+struct Media {
+    static_dir: PathBuf,
+    stored: HashMap<PathBuf, HashMap<Vec<u8>, String>>,
+    written: HashSet<PathBuf>,
+}
+
+impl Media {
+    fn new(static_dir: PathBuf) -> Self {
+        Media {
+            static_dir,
+            stored: HashMap::new(),
+            written: HashSet::new(),
+        }
+    }
+
+    /// Write `contents` as `file_name` into the `bundle` directory or
+    /// `static/uploads` unless the same contents are already there, and
+    /// return the link to the file holding them. A different file
+    /// already written under that name gets a numbered one instead:
+    /// `cat-2.jpg`.
+    fn store(
+        &mut self,
+        bundle: Option<&Path>,
+        file_name: &str,
+        contents: &[u8],
+        fs: &impl Fs,
+    ) -> Result<String> {
+        let (dir, prefix) = match bundle {
+            Some(dir) => (dir.to_owned(), ""),
+            None => (self.static_dir.join("uploads"), "/uploads/"),
+        };
+        let stored = self.stored.entry(dir.clone()).or_default();
+        if let Some(name) = stored.get(contents) {
+            debug!("{} is the same as {}", file_name, name);
+            return Ok(format!("{}{}", prefix, name));
+        }
+
+        let mut name = file_name.to_owned();
+        let (stem, extension) = match file_name.rsplit_once('.') {
+            Some((stem, extension)) if !stem.is_empty() => (stem, format!(".{}", extension)),
+            _ => (file_name, String::new()),
+        };
+        for n in 2.. {
+            if !self.written.contains(&dir.join(&name)) {
+                break;
+            }
+            name = format!("{}-{}{}", stem, n, extension);
+        }
+        if name != file_name {
+            debug!(
+                "{} is taken by another file, storing it as {}",
+                file_name, name
+            );
+        }
+
+        fs.create_dir_all(&dir)?;
+        fs.write_file(&dir.join(&name), contents)?;
+        self.written.insert(dir.join(&name));
+        stored.insert(contents.to_owned(), name.clone());
+        Ok(format!("{}{}", prefix, name))
+    }
+}
+
+/// Copy files linked from `post` out of the local `uploads_dir`, next
+/// to the post for bundles or into `static/uploads` otherwise, and point
+/// the links to the copies.
+fn copy_uploads(
+    post: &mut Post,
+    uploads_dir: &Path,
+    media: &mut Media,
+    options: &Options,
+    fs: &impl Fs,
+    summary: &mut Summary,
+) -> Result<()> {
+    let mut seen = HashSet::new();
+    for url in links(&post.page.markdown) {
+        if !seen.insert(url.clone()) {
+            continue;
+        }
+        let file = match url
+            .split(['?', '#'])
+            .next()
+            .and_then(|url| url.split_once("/wp-content/uploads/"))
+        {
+            Some((_, file)) if !file.is_empty() && !file.ends_with('/') => file,
+            _ => continue,
+        };
+        let file_name = file.rsplit('/').next().unwrap_or(file);
+        let contents = match fs.read_bytes(&uploads_dir.join(file)) {
+            Ok(contents) => contents,
+            Err(error) if error.kind() == ErrorKind::NotFound => {
+                summary.warn(Warning::UnresolvedMedia { link: url });
+                continue;
+            }
+            Err(error) => return Err(error),
+        };
+        let bundle =
+            Some(post.path.parent().expect("no parent in filename")).filter(|_| options.bundles);
+        let link = media.store(bundle, file_name, &contents, fs)?;
+        let url = Regex::new(&regex::escape(&url)).expect("escaped url is a valid regex");
+        post.page.markdown = replace_urls(&post.page.markdown, &url, &link);
+    }
+    Ok(())
+}
+
+/// Distinct post types and statuses of the items in an export with
+/// their counts, to plan a conversion.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct Scan {
+    pub post_types: BTreeMap<String, usize>,
+    pub statuses: BTreeMap<String, usize>,
+}
+
+fn is_known_post_type(name: &str) -> bool {
+    !matches!(PostType::from(name.to_owned()), PostType::Other(_))
+}
+
+fn is_known_status(name: &str) -> bool {
+    !matches!(Status::from(name.to_owned()), Status::Other(_))
+}
+
+impl std::fmt::Display for Scan {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        writeln!(f, "Post types:")?;
+        for (name, count) in &self.post_types {
+            let known = is_known_post_type(name);
+            writeln!(
+                f,
+                "    {}: {}{}",
+                name,
+                count,
+                if known { "" } else { " (unknown)" }
+            )?;
+        }
+        writeln!(f, "Statuses:")?;
+        for (name, count) in &self.statuses {
+            let known = is_known_status(name);
+            writeln!(
+                f,
+                "    {}: {}{}",
+                name,
+                count,
+                if known { "" } else { " (unknown)" }
+            )?;
+        }
+        Ok(())
+    }
+}
+
+/// Count the post types and statuses in `input_files` without
+/// converting anything.
+pub fn scan(input_files: &[PathBuf], fs: &impl Fs) -> Result<Scan> {
+    let mut scan = Scan::default();
+    for input_file in input_files {
+        let rss = read_rss(input_file, fs)?;
+        for item in &rss.channel.item {
+            *scan
+                .post_types
+                .entry(item.post_type.name().to_owned())
+                .or_default() += 1;
+            *scan
+                .statuses
+                .entry(item.status.name().to_owned())
+                .or_default() += 1;
+        }
+    }
+    Ok(scan)
+}
+
+/// Parse `input_files` without converting anything, as a pre-flight
+/// check, and list what they contain that we don't know.
+pub fn check(input_files: &[PathBuf], fs: &impl Fs) -> Result<String> {
+    let scan = scan(input_files, fs)?;
+    let mut report = format!("Parsed {} items\n", scan.post_types.values().sum::<usize>());
+    let unknown = [
+        (
+            "post types",
+            unknown_names(&scan.post_types, is_known_post_type),
+        ),
+        ("statuses", unknown_names(&scan.statuses, is_known_status)),
+    ];
+    for (kind, names) in unknown {
+        if !names.is_empty() {
+            report.push_str(&format!("Unknown {}: {}\n", kind, names.join(", ")));
+        }
+    }
+    Ok(report)
+}
+
+/// Names in `counts` that are not known, with their counts: `wpcode (2)`.
+fn unknown_names(counts: &BTreeMap<String, usize>, is_known: fn(&str) -> bool) -> Vec<String> {
+    counts
+        .iter()
+        .filter(|(name, _)| !is_known(name))
+        .map(|(name, count)| format!("{} ({})", name, count))
+        .collect()
+}
+
+fn read_rss(input_file: &Path, fs: &impl Fs) -> Result<Rss> {
+    from_reader(fs.open(input_file)?).map_err(|error| {
+        Error::new(
+            ErrorKind::InvalidData,
+            format!("cannot parse xml in {}: {}", input_file.display(), error),
+        )
+    })
+}
+
+/// Convert xml given as a string and return generated files with
+/// their contents instead of writing them to disk.
+pub fn convert_str(xml: &str) -> Result<Vec<(PathBuf, String)>> {
+    let fs = MemoryFs::new(xml);
+    convert("".into(), "".into(), &Options::default(), &fs)?;
+    Ok(fs.into_files())
+}
+
+/// Top level wrapper
+#[derive(Debug, Deserialize)]
+struct Rss {
+    channel: Channel,
+}
+
+/// Main wrapper
+#[derive(Debug, Deserialize)]
+struct Channel {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    description: Option<String>,
+    #[serde(rename = "pubDate", default)]
+    pub_date: Option<String>,
+    #[serde(rename = "lastBuildDate", default)]
+    last_build_date: Option<String>,
+    /// Export format version, like `1.2`.
+    #[serde(default)]
+    wxr_version: Option<String>,
+    /// Blog url, the only one in WXR 1.0 exports.
+    #[serde(default)]
+    link: Option<String>,
+    #[serde(default)]
+    base_site_url: Option<String>,
+    /// Categories defined for the whole blog.
+    #[serde(default)]
+    category: Vec<ChannelCategory>,
+    /// Tags defined for the whole blog.
+    #[serde(default)]
+    tag: Vec<ChannelTag>,
+    /// Terms of other taxonomies, and of all of them in some exports.
+    #[serde(default)]
+    term: Vec<ChannelTerm>,
+    /// Absent in empty or filtered exports.
+    #[serde(default)]
+    item: Vec<Item>,
+}
+
+/// Category definition of the channel: `<wp:category>`.
+#[derive(Debug, Deserialize)]
+struct ChannelCategory {
+    #[serde(default)]
+    category_nicename: String,
+    #[serde(default)]
+    cat_name: String,
+    #[serde(default)]
+    category_description: Option<String>,
+}
+
+/// Tag definition of the channel: `<wp:tag>`.
+#[derive(Debug, Deserialize)]
+struct ChannelTag {
+    #[serde(default)]
+    tag_slug: String,
+    #[serde(default)]
+    tag_name: String,
+    #[serde(default)]
+    tag_description: Option<String>,
+}
+
+/// Term definition of the channel: `<wp:term>`.
+#[derive(Debug, Deserialize)]
+struct ChannelTerm {
+    #[serde(default)]
+    term_taxonomy: String,
+    #[serde(default)]
+    term_slug: String,
+    #[serde(default)]
+    term_name: String,
+    #[serde(default)]
+    term_description: Option<String>,
+}
+
+/// Term of a taxonomy as defined for the whole blog.
+#[derive(Debug)]
+struct Term {
+    /// Zola taxonomy, see [`taxonomy`].
+    taxonomy: &'static str,
+    /// WordPress slug.
+    nicename: String,
+    /// Display name.
+    name: String,
+    description: Option<String>,
+}
+
+/// Export format versions we know how to read. Namespaces are ignored
+/// when parsing, so they mostly differ in which elements are present.
+const WXR_VERSIONS: &[&str] = &["1.0", "1.1", "1.2"];
+
+impl Channel {
+    /// Add posts and terms of another part of the same export.
+    fn merge(&mut self, other: Channel) {
+        self.category.extend(other.category);
+        self.tag.extend(other.tag);
+        self.term.extend(other.term);
+        self.item.extend(other.item);
+    }
+
+    fn check_version(&self) {
+        match self.wxr_version.as_deref().map(str::trim) {
+            Some(version) if WXR_VERSIONS.contains(&version) => {
+                debug!("Reading WXR {} export", version)
+            }
+            Some(version) => warn!("Unknown WXR version {}, trying anyway", version),
+            None => warn!("Export has no WXR version, trying anyway"),
+        }
+    }
+
+    /// Url posts links start with.
+    fn base_url(&self) -> String {
+        self.base_site_url
+            .as_deref()
+            .or(self.link.as_deref())
+            .unwrap_or_default()
+            .trim()
+            .to_owned()
+    }
+
+    /// Categories, tags and terms of known taxonomies, without duplicates.
+    fn terms(&self) -> Vec<Term> {
+        let categories = self.category.iter().map(|category| {
+            (
+                "category",
+                &category.category_nicename,
+                &category.cat_name,
+                &category.category_description,
+            )
+        });
+        let tags = self.tag.iter().map(|tag| {
+            (
+                "post_tag",
+                &tag.tag_slug,
+                &tag.tag_name,
+                &tag.tag_description,
+            )
+        });
+        let others = self.term.iter().map(|term| {
+            (
+                term.term_taxonomy.as_str(),
+                &term.term_slug,
+                &term.term_name,
+                &term.term_description,
+            )
+        });
+
+        let mut terms: Vec<Term> = Vec::new();
+        for (domain, nicename, name, description) in categories.chain(tags).chain(others) {
+            let taxonomy = match taxonomy(domain) {
+                Some(taxonomy) => taxonomy,
+                None => continue,
+            };
+            let known = terms
+                .iter()
+                .any(|term| term.taxonomy == taxonomy && &term.nicename == nicename);
+            if !known {
+                terms.push(Term {
+                    taxonomy,
+                    nicename: nicename.clone(),
+                    name: name.clone(),
+                    description: description.clone(),
+                });
+            }
+        }
+        terms
+    }
+
+    /// `lastBuildDate`, or `pubDate` which is when WordPress made the export.
+    fn build_date(&self) -> Option<DateTime<FixedOffset>> {
+        let date = self
+            .last_build_date
+            .as_deref()
+            .or(self.pub_date.as_deref())?;
+        match DateTime::parse_from_rfc2822(date.trim()) {
+            Ok(date) => Some(date),
+            Err(error) => {
+                warn!("Ignoring channel date {}: {}", date, error);
+                None
+            }
+        }
+    }
+}
+
+/// Item can be either Post or Attachment. Broken exports may lack any
+/// element, so all of them have defaults and items missing the ones we
+/// can't do without are skipped.
+#[derive(Debug, Deserialize)]
+struct Item {
+    #[serde(default)]
+    title: String,
+    #[serde(default)]
+    link: Option<String>,
+    #[serde(default)]
+    guid: Option<Guid>,
+    #[serde(default)]
+    category: Vec<Category>,
+    #[serde(default)]
+    post_name: Option<String>,
+    #[serde(default)]
+    post_id: Option<String>,
+    #[serde(default)]
+    post_parent: Option<String>,
+    #[serde(default)]
+    attachment_url: Option<String>,
+    #[serde(rename = "pubDate", default)]
+    pub_date: String,
+    #[serde(default = "PostType::missing")]
+    post_type: PostType,
+    #[serde(default)]
+    encoded: Vec<String>,
+    #[serde(default = "Status::missing")]
+    status: Status,
+    #[serde(default)]
+    post_password: Option<String>,
+    #[serde(default)]
+    post_date_gmt: Option<String>,
+    /// `<dc:creator>`, login of the author.
+    #[serde(default)]
+    creator: Option<String>,
+    /// Local time, in the blog's timezone which the export doesn't name.
+    #[serde(default)]
+    post_date: Option<String>,
+}
+
+impl Item {
+    /// Helper method to workaround serde-xml inability to work with
+    /// fields containing colons.
+    ///
+    /// See https://github.com/RReverser/serde-xml-rs/issues/64
+    fn content(&self) -> &str {
+        self.encoded.first().map_or("", String::as_str)
+    }
+
+    /// Element without which we can't tell what to do with the item.
+    fn missing_field(&self) -> Option<&'static str> {
+        if self.post_type.name().is_empty() {
+            Some("wp:post_type")
+        } else if self.status.name().is_empty() {
+            Some("wp:status")
+        } else {
+            None
+        }
+    }
+
+    /// `<excerpt:encoded>`, the caption of attachments.
+    fn excerpt(&self) -> Option<&str> {
+        self.encoded
+            .get(1)
+            .map(|excerpt| excerpt.trim())
+            .filter(|excerpt| !excerpt.is_empty())
+    }
+
+    /// Title from the `post_name` or the last segment of the link in
+    /// title case: `my-first-post` is `My First Post`.
+    fn slug_title(&self) -> String {
+        let from_link = self.link.as_deref().and_then(|link| {
+            url_path(link)
+                .split(['?', '#'])
+                .next()?
+                .trim_end_matches('/')
+                .rsplit('/')
+                .next()
+                .map(str::to_owned)
+        });
+        match [self.post_name.clone(), from_link]
+            .iter()
+            .flatten()
+            .find(|slug| !slug.trim().is_empty())
+        {
+            Some(slug) => change_case(&slug.replace(['-', '_'], " "), TitleCase::Title),
+            None => "Untitled".to_owned(),
+        }
+    }
+
+    /// Date for items with a broken `pubDate`: the local `post_date` in
+    /// the `timezone` of the blog when known, `post_date_gmt` otherwise.
+    fn wordpress_date(&self, timezone: Option<FixedOffset>) -> Option<DateTime<FixedOffset>> {
+        let gmt = parse_wordpress_date(self.post_date_gmt.as_deref())
+            .map(|date| date.and_utc().fixed_offset());
+        match timezone {
+            Some(timezone) => parse_wordpress_date(self.post_date.as_deref())
+                .and_then(|date| date.and_local_timezone(timezone).single())
+                .or_else(|| gmt.map(|date| date.with_timezone(&timezone))),
+            None => gmt,
+        }
+    }
+
+    /// Slug for an item whose link is taken by another one: its
+    /// `post_name`, or the end of its guid.
+    fn fallback_slug(&self) -> Option<String> {
+        let guid = self
+            .guid
+            .as_ref()
+            .and_then(|guid| guid.value.trim_end_matches('/').rsplit('/').next());
+        [self.post_name.as_deref(), guid]
+            .iter()
+            .flatten()
+            .map(|name| slugify(name))
+            .find(|slug| !slug.is_empty())
+    }
+
+    /// Co-authors added by the Co-Authors Plus plugin as `author` terms,
+    /// or the creator.
+    fn authors(&self) -> Vec<&str> {
+        let coauthors: Vec<&str> = self
+            .category
+            .iter()
+            .filter(|category| category.domain == "author")
+            .map(|category| category.name.trim())
+            .filter(|name| !name.is_empty())
+            .collect();
+        if !coauthors.is_empty() {
+            return coauthors;
+        }
+        self.creator
+            .iter()
+            .map(|creator| creator.trim())
+            .filter(|creator| !creator.is_empty())
+            .collect()
+    }
+
+    /// Whether the item can only be read with a password.
+    fn is_protected(&self) -> bool {
+        self.post_password
+            .as_deref()
+            .is_some_and(|password| !password.is_empty())
+    }
+
+    /// Whether the item's post name or last segment of its link is `slug`.
+    fn has_slug(&self, slug: &str) -> bool {
+        let segment = self
+            .link
+            .as_deref()
+            .and_then(|link| link.trim_end_matches('/').rsplit('/').next());
+        self.post_name.as_deref() == Some(slug) || segment == Some(slug)
+    }
+
+    /// Urls the item was known by: its link and its guid if that's a
+    /// different permalink.
+    fn old_urls(&self) -> Vec<&str> {
+        let mut urls = Vec::new();
+        if let Some(link) = self.link.as_deref().filter(|link| !link.trim().is_empty()) {
+            urls.push(link);
+        }
+        if let Some(guid) = &self.guid {
+            if guid.is_permalink() && !urls.contains(&guid.value.as_str()) {
+                urls.push(&guid.value);
+            }
+        }
+        urls
+    }
+}
+
+/// Category or tag of an item:
+/// `<category domain="category" nicename="c"><![CDATA[C++]]></category>`.
+#[derive(Debug, Deserialize)]
+struct Category {
+    #[serde(default)]
+    domain: String,
+    #[serde(default)]
+    nicename: String,
+    #[serde(rename = "$value", default)]
+    name: String,
+}
+
+impl Category {
+    /// Zola taxonomy for the category's WordPress domain, `bare` for
+    /// categories without one.
+    fn taxonomy(&self, bare: BareCategories) -> Option<&'static str> {
+        match self.domain.as_str() {
+            "" => bare.taxonomy(),
+            domain => taxonomy(domain),
+        }
+    }
+
+    /// Bare categories have no nicename, WordPress would slugify the name.
+    fn nicename(&self) -> String {
+        match self.nicename.as_str() {
+            "" => slugify(&self.name),
+            nicename => nicename.to_owned(),
+        }
+    }
+}
+
+/// Zola taxonomy for a WordPress one: `post_tag` is `tags`.
+fn taxonomy(domain: &str) -> Option<&'static str> {
+    match domain {
+        "category" => Some("categories"),
+        "post_tag" => Some("tags"),
+        _ => None,
+    }
+}
+
+/// `2008-09-01 21:02:27`, the format of `post_date` and `post_date_gmt`.
+fn parse_wordpress_date(date: Option<&str>) -> Option<NaiveDateTime> {
+    NaiveDateTime::parse_from_str(date?, "%Y-%m-%d %H:%M:%S").ok()
+}
+
+/// Globally unique identifier of an item, which may be its permalink.
+#[derive(Debug, Deserialize)]
+struct Guid {
+    #[serde(rename = "isPermaLink", default)]
+    is_permalink: Option<String>,
+    #[serde(rename = "$value", default)]
+    value: String,
+}
+
+impl Guid {
+    /// Guids are permalinks unless told otherwise.
+    fn is_permalink(&self) -> bool {
+        self.is_permalink.as_deref() != Some("false") && self.value.contains("://")
+    }
+}
+
+#[derive(Debug, PartialEq, Eq)]
+enum PostType {
+    Attachment,
+    Post,
+    /// Pages, menu items, plugin types like `wpcode` and such.
+    Other(String),
+}
+
+impl From<String> for PostType {
+    fn from(name: String) -> Self {
+        match name.as_str() {
+            "attachment" => PostType::Attachment,
+            "post" => PostType::Post,
+            _ => PostType::Other(name),
+        }
+    }
+}
+
+impl<'de> Deserialize<'de> for PostType {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(PostType::from)
+    }
+}
+
+impl PostType {
+    /// Stands in for a missing `wp:post_type`.
+    fn missing() -> Self {
+        PostType::Other(String::new())
+    }
+
+    /// Name used in exports, like `attachment`.
+    fn name(&self) -> &str {
+        match self {
+            PostType::Attachment => "attachment",
+            PostType::Post => "post",
+            PostType::Other(name) => name,
+        }
+    }
+}
+
+/// Publication status of an item.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Status {
+    Publish,
+    Draft,
+    Inherit,
+    Private,
+    Pending,
+    Future,
+    Trash,
+    AutoDraft,
+    /// Statuses added by plugins, like `wc-completed`.
+    Other(String),
+}
+
+impl From<String> for Status {
+    fn from(name: String) -> Self {
+        Status::ALL
+            .iter()
+            .find(|status| status.name() == name)
+            .cloned()
+            .unwrap_or(Status::Other(name))
+    }
+}
+
+impl<'de> Deserialize<'de> for Status {
+    fn deserialize<D>(deserializer: D) -> std::result::Result<Self, D::Error>
+    where
+        D: serde::Deserializer<'de>,
+    {
+        String::deserialize(deserializer).map(Status::from)
+    }
+}
+
+impl Status {
+    const ALL: [Status; 8] = [
+        Status::Publish,
+        Status::Draft,
+        Status::Inherit,
+        Status::Private,
+        Status::Pending,
+        Status::Future,
+        Status::Trash,
+        Status::AutoDraft,
+    ];
+
+    /// Stands in for a missing `wp:status`.
+    fn missing() -> Self {
+        Status::Other(String::new())
+    }
+
+    /// Whether the item is unfinished work rather than published.
+    fn is_draft(&self) -> bool {
+        matches!(self, Status::Draft | Status::AutoDraft | Status::Pending)
+    }
+
+    /// Name used in exports, like `auto-draft`.
+    pub fn name(&self) -> &str {
+        match self {
+            Status::Publish => "publish",
+            Status::Draft => "draft",
+            Status::Inherit => "inherit",
+            Status::Private => "private",
+            Status::Pending => "pending",
+            Status::Future => "future",
+            Status::Trash => "trash",
+            Status::AutoDraft => "auto-draft",
+            Status::Other(name) => name,
+        }
+    }
+}
+
+impl std::fmt::Display for Status {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(self.name())
+    }
+}
+
+impl FromStr for Status {
+    type Err = String;
+
+    fn from_str(name: &str) -> std::result::Result<Self, Self::Err> {
+        Status::ALL
+            .iter()
+            .find(|status| status.name() == name)
+            .cloned()
+            .ok_or_else(|| {
+                let names: Vec<&str> = Status::ALL.iter().map(|status| status.name()).collect();
+                format!(
+                    "Unknown status {}, expected one of {}",
+                    name,
+                    names.join(", ")
+                )
+            })
+    }
+}
+
+/// File system operations needed to produce zola content.
+pub trait Fs {
+    fn open(&self, path: &Path) -> Result<impl Read>;
+
+    /// Read a whole text file, `None` if it doesn't exist.
+    fn read_file(&self, path: &Path) -> Result<Option<String>>;
+
+    fn create_dir_all<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>;
+
+    /// Write the page at `path`. A page file is either complete or
+    /// absent, even if writing it is interrupted.
+    fn create_page(&self, path: &Path, page: &Page) -> Result<()>;
+
+    /// Create the `_index.md` file at `path`.
+    fn create_section(&self, path: &Path, section: &Section) -> Result<()>;
+
+    /// Fetch the contents of a file from `url`.
+    fn fetch(&self, url: &str) -> Result<Vec<u8>>;
+
+    /// Read a whole local file, like a backed up upload.
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>>;
+
+    /// Write a media file.
+    fn write_file(&self, path: &Path, contents: &[u8]) -> Result<()>;
+
+    fn create_config(&self, path: &Path, config: &Config) -> Result<()>;
+
+    /// Write a list of links, one per line.
+    fn create_links(&self, path: &Path, links: &[String]) -> Result<()>;
+
+    /// Write old urls and where they went as json.
+    fn create_manifest(&self, path: &Path, entries: &[ManifestEntry]) -> Result<()>;
+
+    /// Write converted pages as csv.
+    fn create_index(&self, path: &Path, entries: &[IndexEntry]) -> Result<()>;
+
+    /// Write the html a page was converted from.
+    fn create_html(&self, path: &Path, html: &str) -> Result<()>;
+}
+
+pub struct RealFs {}
+
+impl Fs for RealFs {
+    fn open(&self, path: &Path) -> Result<impl Read> {
+        File::open(path)
+    }
+
+    fn read_file(&self, path: &Path) -> Result<Option<String>> {
+        match std::fs::read_to_string(path) {
+            Ok(contents) => Ok(Some(contents)),
+            Err(error) if error.kind() == ErrorKind::NotFound => Ok(None),
+            Err(error) => Err(error),
+        }
+    }
+
+    fn create_dir_all<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        create_dir_all(path)
+    }
+
+    /// Create post file
+    fn create_page(&self, path: &Path, page: &Page) -> Result<()> {
+        write_atomically(path, |file| file.write_all(page_contents(page).as_bytes()))
+    }
+
+    /// Create section `_index.md` file.
+    fn create_section(&self, path: &Path, section: &Section) -> Result<()> {
+        write_atomically(path, |file| {
+            file.write_all(section_contents(section).as_bytes())
+        })
+    }
+
+    fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        let mut contents = Vec::new();
+        fetch(url)?.read_to_end(&mut contents)?;
+        Ok(contents)
+    }
+
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        std::fs::read(path)
+    }
+
+    fn write_file(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        write_atomically(path, |file| file.write_all(contents))
+    }
+
+    /// Create site `config.toml` file.
+    fn create_config(&self, path: &Path, config: &Config) -> Result<()> {
+        write_atomically(path, |file| {
+            file.write_all(config_contents(config).as_bytes())
+        })
+    }
+
+    fn create_links(&self, path: &Path, links: &[String]) -> Result<()> {
+        write_atomically(path, |file| {
+            file.write_all(links_contents(links).as_bytes())
+        })
+    }
+
+    fn create_manifest(&self, path: &Path, entries: &[ManifestEntry]) -> Result<()> {
+        write_atomically(path, |file| {
+            file.write_all(manifest_contents(entries).as_bytes())
+        })
+    }
+
+    fn create_index(&self, path: &Path, entries: &[IndexEntry]) -> Result<()> {
+        write_atomically(path, |file| {
+            file.write_all(index_contents(entries).as_bytes())
+        })
+    }
+
+    fn create_html(&self, path: &Path, html: &str) -> Result<()> {
+        write_atomically(path, |file| file.write_all(html.as_bytes()))
+    }
+}
+
+/// Write a file next to `path` and rename it into place, so that `path`
+/// is either complete or absent even if we are killed mid-write. The
+/// temporary file is a hidden `.name.tmp`, which zola ignores.
+fn write_atomically(path: &Path, write: impl FnOnce(&mut File) -> Result<()>) -> Result<()> {
+    let name = path.file_name().ok_or_else(|| {
+        Error::new(
+            ErrorKind::InvalidInput,
+            format!("{} is not a file", path.display()),
+        )
+    })?;
+    let temp = path.with_file_name(format!(".{}.tmp", name.to_string_lossy()));
+    let result = File::create(&temp)
+        .and_then(|mut file| {
+            write(&mut file)?;
+            file.sync_all()
+        })
+        .and_then(|()| std::fs::rename(&temp, path));
+    if result.is_err() {
+        let _ = std::fs::remove_file(&temp);
+    }
+    result
+}
+
+/// Start downloading `url`.
+fn fetch(url: &str) -> Result<impl Read> {
+    let response = ureq::get(url).call().map_err(Error::other)?;
+    Ok(response.into_reader())
+}
+
+/// Keeps generated files in memory instead of writing them to disk.
+pub struct MemoryFs {
+    input: String,
+    /// Extra readable files, like section templates.
+    inputs: HashMap<PathBuf, String>,
+    files: RefCell<Vec<(PathBuf, String)>>,
+}
+
+impl MemoryFs {
+    pub fn new(input: &str) -> Self {
+        Self {
+            input: input.to_owned(),
+            inputs: HashMap::new(),
+            files: RefCell::new(Vec::new()),
+        }
+    }
+
+    /// Make `contents` readable at `path`.
+    pub fn with_file(mut self, path: impl Into<PathBuf>, contents: &str) -> Self {
+        self.inputs.insert(path.into(), contents.to_owned());
+        self
+    }
+
+    /// Generated files in the order they were created.
+    pub fn into_files(self) -> Vec<(PathBuf, String)> {
+        self.files.into_inner()
+    }
+}
+
+impl Fs for MemoryFs {
+    fn open(&self, path: &Path) -> Result<impl Read> {
+        Ok(self.inputs.get(path).unwrap_or(&self.input).as_bytes())
+    }
+
+    fn read_file(&self, path: &Path) -> Result<Option<String>> {
+        Ok(self.inputs.get(path).cloned())
+    }
+
+    fn create_dir_all<P>(&self, _path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        Ok(())
+    }
+
+    fn create_page(&self, path: &Path, page: &Page) -> Result<()> {
+        self.files
+            .borrow_mut()
+            .push((path.to_owned(), page_contents(page)));
+        Ok(())
+    }
+
+    fn create_section(&self, path: &Path, section: &Section) -> Result<()> {
+        self.files
+            .borrow_mut()
+            .push((path.to_owned(), section_contents(section)));
+        Ok(())
+    }
+
+    fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        Err(Error::new(
+            ErrorKind::Unsupported,
+            format!("cannot download {} in memory", url),
+        ))
+    }
+
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        match self.inputs.get(path) {
+            Some(contents) => Ok(contents.clone().into_bytes()),
+            None => Err(Error::new(
+                ErrorKind::NotFound,
+                format!("{} not found", path.display()),
+            )),
+        }
+    }
+
+    fn write_file(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        self.files.borrow_mut().push((
+            path.to_owned(),
+            String::from_utf8_lossy(contents).into_owned(),
+        ));
+        Ok(())
+    }
+
+    fn create_config(&self, path: &Path, config: &Config) -> Result<()> {
+        self.files
+            .borrow_mut()
+            .push((path.to_owned(), config_contents(config)));
+        Ok(())
+    }
+
+    fn create_links(&self, path: &Path, links: &[String]) -> Result<()> {
+        self.files
+            .borrow_mut()
+            .push((path.to_owned(), links_contents(links)));
+        Ok(())
+    }
+
+    fn create_manifest(&self, path: &Path, entries: &[ManifestEntry]) -> Result<()> {
+        self.files
+            .borrow_mut()
+            .push((path.to_owned(), manifest_contents(entries)));
+        Ok(())
+    }
+
+    fn create_index(&self, path: &Path, entries: &[IndexEntry]) -> Result<()> {
+        self.files
+            .borrow_mut()
+            .push((path.to_owned(), index_contents(entries)));
+        Ok(())
+    }
+
+    fn create_html(&self, path: &Path, html: &str) -> Result<()> {
+        self.files
+            .borrow_mut()
+            .push((path.to_owned(), html.to_owned()));
+        Ok(())
+    }
+}
+
+/// Render post file: front-matter followed by content.
+fn page_contents(page: &Page) -> String {
+    let mut contents = String::new();
+    contents.push_str("+++\n");
+    contents.push_str(&format!("title = {}\n", quote(&page.title)));
+    contents.push_str(&format!("date = {}\n", page.date.to_rfc3339()));
+    if page.draft {
+        contents.push_str("draft = true\n");
+    }
+    if let Some(weight) = page.weight {
+        contents.push_str(&format!("weight = {}\n", weight));
+    }
+    if !page.aliases.is_empty() {
+        let aliases: Vec<String> = page.aliases.iter().map(|alias| quote(alias)).collect();
+        contents.push_str(&format!("aliases = [{}]\n", aliases.join(", ")));
+    }
+    for (key, value) in &page.fields {
+        contents.push_str(&format!("{} = {}\n", key, value));
+    }
+    if !page.taxonomies.is_empty() {
+        contents.push_str("\n[taxonomies]\n");
+        for (taxonomy, terms) in &page.taxonomies {
+            let terms: Vec<String> = terms.iter().map(|term| quote(term)).collect();
+            contents.push_str(&format!("{} = [{}]\n", taxonomy, terms.join(", ")));
+        }
+    }
+    if !page.extra.is_empty() {
+        contents.push_str("\n[extra]\n");
+        for (key, value) in &page.extra {
+            contents.push_str(&format!("{} = {}\n", key, value));
+        }
+    }
+    contents.push_str("+++\n");
+    contents.push_str(&page.markdown);
+    contents.push('\n');
+    contents
+}
+
+/// Render section `_index.md` file.
+fn section_contents(section: &Section) -> String {
+    if let Some(template) = &section.template {
+        return template.clone();
+    }
+    let mut contents = String::new();
+    contents.push_str("+++\n");
+    if let Some(title) = &section.title {
+        contents.push_str(&format!("title = {}\n", quote(title)));
+    }
+    if let Some(description) = &section.description {
+        contents.push_str(&format!("description = {}\n", quote(description)));
+    }
+    if section.transparent {
+        contents.push_str("transparent = true\n"); // show pages from this section in index.html
+    }
+    contents.push_str(&format!("sort_by = \"{}\"\n", section.sort_by));
+    if section.paginate_by > 0 {
+        contents.push_str(&format!("paginate_by = {}\n", section.paginate_by));
+    }
+    contents.push_str("+++\n");
+    if !section.markdown.is_empty() {
+        contents.push_str(&section.markdown);
+        contents.push('\n');
+    }
+    contents
+}
+
+/// Render site `config.toml` file.
+fn config_contents(config: &Config) -> String {
+    let mut contents = String::new();
+    contents.push_str(&format!("base_url = {}\n", quote(&config.base_url)));
+    contents.push_str(&format!("title = {}\n", quote(&config.title)));
+    if let Some(description) = &config.description {
+        contents.push_str(&format!("description = {}\n", quote(description)));
+    }
+    if !config.taxonomies.is_empty() {
+        let names: Vec<String> = config
+            .taxonomies
+            .keys()
+            .map(|name| format!("{{ name = \"{}\" }}", name))
+            .collect();
+        contents.push_str(&format!("taxonomies = [{}]\n", names.join(", ")));
+    }
+    contents.push_str("\n[extra]\n");
+    // remember how the site was made
+    contents.push_str(&format!(
+        "generator = \"wordpress-to-zola {}\"\n",
+        env!("CARGO_PKG_VERSION")
+    ));
+    contents.push_str(&format!(
+        "generated_at = {}\n",
+        config
+            .generated_at
+            .to_rfc3339_opts(SecondsFormat::Secs, true)
+    ));
+    if let Some(date) = config.last_build_date {
+        contents.push_str(&format!("last_build_date = {}\n", date.to_rfc3339()));
+    }
+    contents.push_str(&format!("source = {}\n", quote(&config.source)));
+    for (taxonomy, terms) in &config.taxonomies {
+        contents.push_str(&format!("\n[extra.nicenames.{}]\n", taxonomy));
+        for (nicename, name) in terms {
+            contents.push_str(&format!("{} = {}\n", quote(nicename), quote(name)));
+        }
+    }
+    contents
+}
+
+/// Render a list of links, one per line.
+fn links_contents(links: &[String]) -> String {
+    links.iter().map(|link| format!("{}\n", link)).collect()
+}
+
+/// Render manifest entries as a json object keyed by old url.
+fn manifest_contents(entries: &[ManifestEntry]) -> String {
+    let fields: Vec<String> = entries
+        .iter()
+        .map(|entry| {
+            format!(
+                "  {}: {{ \"path\": {}, \"slug\": {} }}",
+                quote(&entry.old_url),
+                quote(&entry.path),
+                quote(&entry.slug)
+            )
+        })
+        .collect();
+    if fields.is_empty() {
+        return "{}\n".to_owned();
+    }
+    format!("{{\n{}\n}}\n", fields.join(",\n"))
+}
+
+/// Render index entries as csv with a header, several authors or tags
+/// separated by `;`.
+fn index_contents(entries: &[IndexEntry]) -> String {
+    let mut contents = "title,date,slug,path,author,tags\n".to_owned();
+    for entry in entries {
+        let fields = [
+            entry.title.clone(),
+            entry.date.to_rfc3339(),
+            entry.slug.clone(),
+            entry.path.clone(),
+            entry.authors.join(";"),
+            entry.tags.join(";"),
+        ];
+        let fields: Vec<String> = fields.iter().map(|field| csv_field(field)).collect();
+        contents.push_str(&fields.join(","));
+        contents.push('\n');
+    }
+    contents
+}
+
+/// Quote `text` as a csv field if it has commas, quotes or line breaks.
+fn csv_field(text: &str) -> String {
+    if text.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", text.replace('"', "\"\""))
+    } else {
+        text.to_owned()
+    }
+}
+
+/// Quote and escape `text` as a json string, or a toml basic string,
+/// which escape the same way.
+fn quote(text: &str) -> String {
+    let mut json = String::with_capacity(text.len() + 2);
+    json.push('"');
+    for c in text.chars() {
+        match c {
+            '"' => json.push_str("\\\""),
+            '\\' => json.push_str("\\\\"),
+            '\n' => json.push_str("\\n"),
+            c if c.is_control() => json.push_str(&format!("\\u{:04x}", c as u32)),
+            c => json.push(c),
+        }
+    }
+    json.push('"');
+    json
+}
+
+/// Template for section `name` in `dir`: `blog` uses `blog.md`, the
+/// root section uses `_index.md`.
+fn section_template_path(dir: &Path, name: &Path) -> PathBuf {
+    if name.as_os_str().is_empty() {
+        dir.join("_index.md")
+    } else {
+        dir.join(name).with_extension("md")
+    }
+}
+
+/// Pick a path for an item. Items with flat permalinks like `/post/`
+/// or without a link at all are put in the default section, the
+/// latter using their post name or title as a slug.
+fn item_path(base_url: &str, item: &Item, options: &Options) -> Option<PathBuf> {
+    let link_path = item
+        .link
+        .as_deref()
+        .filter(|link| !link.trim().is_empty())
+        .map(|link| generate_path(base_url, link))
+        .unwrap_or_default();
+    let link_path = match options.max_depth {
+        Some(depth) => collapse_path(&link_path, depth),
+        None => link_path,
+    };
+    if let Some((dir, slug)) = link_path.rsplit_once('/') {
+        return Some(PathBuf::from(dir).join(file_name(slug, &options.extension)));
+    }
+    let slug = if link_path.is_empty() {
+        [item.post_name.as_deref(), Some(item.title.as_str())]
+            .iter()
+            .flatten()
+            .map(|name| slugify(name))
+            .find(|slug| !slug.is_empty())?
+    } else {
+        link_path
+    };
+    Some(PathBuf::from(&options.default_section).join(file_name(&slug, &options.extension)))
+}
+
+/// `path`, or `path` with a number added to its slug if it is `taken`
+/// already: `post-2.md`.
+fn unique_path(path: PathBuf, taken: &HashSet<PathBuf>, extension: &str) -> PathBuf {
+    let slug = path
+        .file_stem()
+        .map(|stem| stem.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut unique = path.clone();
+    for n in 2.. {
+        if !taken.contains(&unique) {
+            break;
+        }
+        unique = path.with_file_name(file_name(&format!("{}-{}", slug, n), extension));
+    }
+    unique
+}
+
+/// Join the parts of `path` below `depth` directories with dashes.
+fn collapse_path(path: &str, depth: usize) -> String {
+    let parts: Vec<&str> = path.split('/').collect();
+    if parts.len() <= depth + 1 {
+        return path.to_owned();
+    }
+    let (dirs, rest) = parts.split_at(depth);
+    let slug = rest.join("-");
+    let mut parts = dirs.to_vec();
+    parts.push(&slug);
+    parts.join("/")
+}
+
+/// Longest file name most file systems allow, in bytes.
+const MAX_FILE_NAME: usize = 255;
+
+/// `slug.extension`, with overly long slugs cut short and told apart
+/// by a hash of the whole slug: `very-long-...-1f2e3d4c.md`.
+fn file_name(slug: &str, extension: &str) -> String {
+    let name = format!("{}.{}", slug, extension);
+    if name.len() <= MAX_FILE_NAME {
+        return name;
+    }
+    let mut hasher = DefaultHasher::new();
+    slug.hash(&mut hasher);
+    let hash = format!("{:08x}", hasher.finish() as u32);
+
+    let mut end = MAX_FILE_NAME - extension.len() - hash.len() - 2;
+    while !slug.is_char_boundary(end) {
+        end -= 1;
+    }
+    format!("{}-{}.{}", &slug[..end], hash, extension)
+}
+
+/// Move a page from the section of its link into the one `options`
+/// ask for: `blog/post.md` is `drafts/post.md` for a draft, or
+/// `2019/post.md` when grouping by year.
+fn group_path(
+    path: PathBuf,
+    status: &Status,
+    date: &DateTime<FixedOffset>,
+    options: &Options,
+) -> PathBuf {
+    let file_name = path.file_name().expect("no file name in path");
+    if options.drafts && status.is_draft() {
+        return PathBuf::from("drafts").join(file_name);
+    }
+    match options.group_by {
+        Some(GroupBy::Year) => PathBuf::from(date.format("%Y").to_string()).join(file_name),
+        None => path,
+    }
+}
+
+/// Generate path for an item by splicing base url from the link:
+/// `http://a.com/blog/post/` is `blog/post`.
+fn generate_path(base_url: &str, link: &str) -> String {
+    let path = if is_internal(base_url, link) && !is_root_relative(link) {
+        let site = strip_scheme(base_url).trim_end_matches('/');
+        strip_scheme(link)[site.len()..].to_owned()
+    } else {
+        url_path(link)
+    };
+    path.trim_matches('/').to_owned()
+}
+
+/// Whether `link` points to the site at `base_url`, whatever the scheme.
+fn is_internal(base_url: &str, link: &str) -> bool {
+    if is_root_relative(link) {
+        return true;
+    }
+    let site = strip_scheme(base_url).trim_end_matches('/');
+    !site.is_empty()
+        && strip_scheme(link)
+            .strip_prefix(site)
+            .is_some_and(|rest| rest.is_empty() || rest.starts_with(['/', '?', '#']))
+}
+
+/// The blog of a multisite export that `link` belongs to, the most
+/// specific one if they nest.
+fn blog_of<'a>(blogs: &'a [String], link: &str) -> Option<&'a str> {
+    blogs
+        .iter()
+        .filter(|blog| !is_root_relative(link) && is_internal(blog, link))
+        .max_by_key(|blog| strip_scheme(blog).trim_end_matches('/').len())
+        .map(String::as_str)
+}
+
+/// Directory of a blog in a multisite export: `example.com/travel` is
+/// `travel` and `travel.example.com` is `travel`.
+fn blog_name(blog: &str) -> String {
+    let site = strip_scheme(blog).trim_end_matches('/');
+    match site.rsplit_once('/') {
+        Some((_, name)) => name.to_owned(),
+        None => site.split('.').next().unwrap_or(site).to_owned(),
+    }
+}
+
+/// `/b` is resolved against the site, `//a.com/b` is not.
+fn is_root_relative(link: &str) -> bool {
+    link.starts_with('/') && !link.starts_with("//")
+}
+
+/// Replace the matches of `url` in `text` with `link`, except
+/// root-relative matches that are the tail of some other absolute url.
+fn replace_urls(text: &str, url: &Regex, link: &str) -> String {
+    let mut replaced = String::with_capacity(text.len());
+    let mut last = 0;
+    for found in url.find_iter(text) {
+        let tail = is_root_relative(found.as_str())
+            && text[..found.start()]
+                .chars()
+                .next_back()
+                .is_some_and(|c| !c.is_whitespace() && !matches!(c, '(' | '<' | '"' | '\'' | '='));
+        if tail {
+            continue;
+        }
+        replaced.push_str(&text[last..found.start()]);
+        replaced.push_str(link);
+        last = found.end();
+    }
+    replaced.push_str(&text[last..]);
+    replaced
+}
+
+/// `https://a.com/b` is `a.com/b`.
+fn strip_scheme(url: &str) -> &str {
+    url.split_once("://").map_or(url, |(_, rest)| rest)
+}
+
+/// Path and query of an absolute url: `http://a.com/b/?p=1` is `/b/?p=1`.
+fn url_path(url: &str) -> String {
+    let without_scheme = strip_scheme(url);
+    match without_scheme.find(['/', '?']) {
+        Some(start) if without_scheme[start..].starts_with('/') => {
+            without_scheme[start..].to_owned()
+        }
+        Some(start) => format!("/{}", &without_scheme[start..]),
+        None => "/".to_owned(),
+    }
+}
+
+/// Change the case of `title`. Words like `WordPress` or `HTML` keep
+/// their case, unless the whole title is upper case.
+fn change_case(title: &str, case: TitleCase) -> String {
+    let shouting = !title.chars().any(char::is_lowercase);
+    let mut words = Vec::new();
+    for (i, word) in title.split(' ').enumerate() {
+        let mut chars = word.chars();
+        let keep = !shouting && chars.clone().skip(1).any(char::is_uppercase);
+        let word = match chars.next() {
+            _ if keep && case != TitleCase::Lower => word.to_owned(),
+            Some(first) if case == TitleCase::Title || (case == TitleCase::Sentence && i == 0) => {
+                first
+                    .to_uppercase()
+                    .chain(chars.as_str().to_lowercase().chars())
+                    .collect()
+            }
+            _ => word.to_lowercase(),
+        };
+        words.push(word);
+    }
+    words.join(" ")
+}
+
+/// Display name of the category with the section's `slug`, or the slug
+/// in title case: `my-travels` is `My Travels`.
+fn section_title(slug: &str, category_names: &BTreeMap<String, String>) -> String {
+    match category_names.get(slug) {
+        Some(name) => name.clone(),
+        None => change_case(&slug.replace(['-', '_'], " "), TitleCase::Title),
+    }
+}
+
+/// Make a lowercase, dash separated slug out of `text`.
+fn slugify(text: &str) -> String {
+    text.to_lowercase()
+        .split(|c: char| !c.is_alphanumeric())
+        .filter(|word| !word.is_empty())
+        .collect::<Vec<_>>()
+        .join("-")
+}
+
+#[cfg(test)]
+mod tests {
+    use std::cell::RefCell;
+    use std::io::Write;
+
+    use crate::{
+        change_case, convert, convert_all_with, convert_str, convert_with, BareCategories, Config,
+        DateField, Fs, GroupBy, IndexEntry, ManifestEntry, MemoryFs, NextPage, Options, Page,
+        PostType, Protected, Regex, Rss, Section, Status, Summary, TitleCase, TrailingSlash,
+        Warning,
+    };
+
+    struct FakeFs {
+        input: String,
+        calls: RefCell<Vec<String>>,
+    }
+
+    impl FakeFs {
+        fn new(input: &str) -> Self {
+            Self {
+                input: input.to_owned(),
+                calls: RefCell::new(Vec::new()),
+            }
+        }
+
+        fn calls(&self) -> Vec<String> {
+            self.calls.borrow().clone()
+        }
+    }
+
+    impl Fs for FakeFs {
+        fn open(&self, _path: &std::path::Path) -> std::io::Result<impl std::io::Read> {
+            Ok(self.input.as_bytes())
+        }
+
+        fn read_file(&self, _path: &std::path::Path) -> std::io::Result<Option<String>> {
+            Ok(None)
+        }
+
+        fn create_dir_all<P>(&self, path: P) -> std::io::Result<()>
+        where
+            P: AsRef<std::path::Path>,
+        {
+            self.calls
+                .borrow_mut()
+                .push(format!("create_dir_all({:?})", path.as_ref()));
+            Ok(())
+        }
+
+        fn create_page(&self, path: &std::path::Path, page: &Page) -> std::io::Result<()> {
+            self.calls.borrow_mut().push(format!(
+                "create_page({:?}, {}, {}, {})",
+                path, page.title, page.date, page.markdown
+            ));
+            Ok(())
+        }
+
+        fn create_section(
+            &self,
+            path: &std::path::Path,
+            _section: &Section,
+        ) -> std::io::Result<()> {
+            self.calls
+                .borrow_mut()
+                .push(format!("create_section({:?})", path));
+            Ok(())
+        }
+
+        /// Every url has the same contents.
+        fn fetch(&self, url: &str) -> std::io::Result<Vec<u8>> {
+            self.calls.borrow_mut().push(format!("fetch({})", url));
+            Ok(b"image".to_vec())
+        }
+
+        fn read_bytes(&self, path: &std::path::Path) -> std::io::Result<Vec<u8>> {
+            self.calls
+                .borrow_mut()
+                .push(format!("read_bytes({:?})", path));
+            Ok(path.to_string_lossy().into_owned().into_bytes())
+        }
+
+        fn write_file(&self, path: &std::path::Path, _contents: &[u8]) -> std::io::Result<()> {
+            self.calls
+                .borrow_mut()
+                .push(format!("write_file({:?})", path));
+            Ok(())
+        }
+
+        fn create_config(&self, path: &std::path::Path, _config: &Config) -> std::io::Result<()> {
+            self.calls
+                .borrow_mut()
+                .push(format!("create_config({:?})", path));
+            Ok(())
+        }
+
+        fn create_links(&self, path: &std::path::Path, links: &[String]) -> std::io::Result<()> {
+            self.calls
+                .borrow_mut()
+                .push(format!("create_links({:?}, {:?})", path, links));
+            Ok(())
+        }
+
+        fn create_manifest(
+            &self,
+            path: &std::path::Path,
+            entries: &[ManifestEntry],
+        ) -> std::io::Result<()> {
+            self.calls
+                .borrow_mut()
+                .push(format!("create_manifest({:?}, {:?})", path, entries));
+            Ok(())
+        }
+
+        fn create_index(
+            &self,
+            path: &std::path::Path,
+            entries: &[IndexEntry],
+        ) -> std::io::Result<()> {
+            self.calls
+                .borrow_mut()
+                .push(format!("create_index({:?}, {:?})", path, entries));
+            Ok(())
+        }
+
+        fn create_html(&self, path: &std::path::Path, html: &str) -> std::io::Result<()> {
+            self.calls
+                .borrow_mut()
+                .push(format!("create_html({:?}, {})", path, html));
+            Ok(())
+        }
+    }
+
+    /// Contents of the generated file at `path`.
+    fn file<'a>(files: &'a [(std::path::PathBuf, String)], path: &str) -> &'a str {
+        files
+            .iter()
+            .find(|(file, _)| file.as_path() == std::path::Path::new(path))
+            .map(|(_, contents)| contents.as_str())
+            .unwrap_or_else(|| panic!("{} was not generated", path))
+    }
+
+    #[test]
+    fn normal_posts_are_converted() {
+        // Given a WP export with a post in it
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = FakeFs::new(input);
+        convert("".into(), "output".into(), &Options::default(), &fs).unwrap();
+
+        // Then we create a post and section
+        assert_eq!(
+            fs.calls(),
+            &[
+                "create_dir_all(\"output/posts\")",
+                "create_section(\"output/_index.md\")",
+                "create_section(\"output/posts/_index.md\")",
+                "create_page(\
+                    \"output/posts/post1.md\", \
+                    Post 1, \
+                    2008-09-01 21:02:27 +00:00, \
+                    Hello\
+                )",
+            ]
+        );
+    }
+
+    #[test]
+    fn unknown_post_types_are_ignored() {
+        // Given a blog item wpcode post_tyoe
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[wpcode]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = FakeFs::new(input);
+        convert("".into(), "output".into(), &Options::default(), &fs).unwrap();
+
+        // Then nothing was generated
+        assert!(fs.calls().is_empty());
+    }
+
+    #[test]
+    fn unknown_post_types_keep_their_name() {
+        // Given a blog item of a plugin's post type
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Snippet</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[wpcode]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we parse it
+        let rss: Rss = serde_xml_rs::from_str(input).unwrap();
+
+        // Then the post type tells which one it was
+        assert_eq!(
+            rss.channel.item[0].post_type,
+            PostType::Other("wpcode".to_owned())
+        );
+    }
+
+    #[test]
+    fn quotes_in_titles_are_escaped() {
+        // Given a blog item with quotes in its title
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post "1"</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = FakeFs::new(input);
+        convert("".into(), "output".into(), &Options::default(), &fs).unwrap();
+
+        // Then the created post keeps the title as it is
+        assert_eq!(
+            fs.calls(),
+            &[
+                "create_dir_all(\"output/posts\")",
+                "create_section(\"output/_index.md\")",
+                "create_section(\"output/posts/_index.md\")",
+                "create_page(\
+                    \"output/posts/post1.md\", \
+                    Post \"1\", \
+                    2008-09-01 21:02:27 +00:00, \
+                    Hello\
+                )",
+            ]
+        );
+
+        // And its file escapes the quotes
+        let files = convert_str(input).unwrap();
+        assert!(file(&files, "posts/post1.md").starts_with("+++\ntitle = \"Post \\\"1\\\"\"\n"));
+    }
+
+    #[test]
+    fn titles_change_case() {
+        assert_eq!(
+            change_case("HELLO FROM THE WORDPRESS WORLD", TitleCase::Sentence),
+            "Hello from the wordpress world"
+        );
+        assert_eq!(
+            change_case("why I like WordPress and HTML", TitleCase::Title),
+            "Why I Like WordPress And HTML"
+        );
+        assert_eq!(change_case("Mostly Fine", TitleCase::Lower), "mostly fine");
+    }
+
+    #[test]
+    fn paragraphs_are_separated() {
+        // Given a blog item with two paragraphs
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post "1"</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/post1</link>
+                    <content:encoded><![CDATA[para a
+
+para b]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = FakeFs::new(input);
+        convert("".into(), "output".into(), &Options::default(), &fs).unwrap();
+
+        // Then the created post contains separate paragraphs
+        assert_eq!(
+            fs.calls(),
+            &[
+                "create_dir_all(\"output/posts\")",
+                "create_section(\"output/_index.md\")",
+                "create_section(\"output/posts/_index.md\")",
+                "create_page(\
+                    \"output/posts/post1.md\", \
+                    Post \"1\", \
+                    2008-09-01 21:02:27 +00:00, \
+                    para a\n\npara b\
+                )",
+            ]
+        );
+    }
+
+    #[test]
+    fn convert_str_returns_files_in_memory() {
+        // Given a WP export with a post in it
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it in memory
+        let files = convert_str(input).unwrap();
+
+        // Then we get the homepage, the section and the page with its front matter
+        assert_eq!(
+            files,
+            &[
+                (
+                    "_index.md".into(),
+                    "+++\n\
+                     title = \"Blog\"\n\
+                     sort_by = \"date\"\n\
+                     paginate_by = 5\n\
+                     +++\n"
+                        .to_owned()
+                ),
+                (
+                    "blog/_index.md".into(),
+                    "+++\n\
+                     title = \"Blog\"\n\
+                     transparent = true\n\
+                     sort_by = \"date\"\n\
+                     paginate_by = 5\n\
+                     +++\n"
+                        .to_owned()
+                ),
+                (
+                    "blog/post1.md".into(),
+                    "+++\n\
+                     title = \"Post 1\"\n\
+                     date = 2008-09-01T21:02:27+00:00\n\
+                     +++\n\
+                     Hello\n"
+                        .to_owned()
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn posts_without_link_go_to_default_section() {
+        // Given a blog item without a link
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Hello, World!</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = FakeFs::new(input);
+        let options = Options {
+            default_section: "blog".to_owned(),
+            ..Options::default()
+        };
+        convert("".into(), "output".into(), &options, &fs).unwrap();
+
+        // Then the post is named after its title and put in the default section
+        assert_eq!(
+            fs.calls(),
+            &[
+                "create_dir_all(\"output/blog\")",
+                "create_section(\"output/_index.md\")",
+                "create_section(\"output/blog/_index.md\")",
+                "create_page(\
+                    \"output/blog/hello-world.md\", \
+                    Hello, World!, \
+                    2008-09-01 21:02:27 +00:00, \
+                    Hello\
+                )",
+            ]
+        );
+    }
+
+    #[test]
+    fn weights_follow_reverse_chronological_order() {
+        // Given two posts of different dates in one section
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Old</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/work/old</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>New</title>
+                    <pubDate>Tue, 02 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/work/new</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with weights
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            weights: true,
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the newest post comes first and the section sorts by weight
+        let files = fs.into_files();
+        assert!(file(&files, "work/_index.md").contains("sort_by = \"weight\"\n"));
+        assert!(file(&files, "work/new.md").contains("weight = 1\n"));
+        assert!(file(&files, "work/old.md").contains("weight = 2\n"));
+    }
+
+    #[test]
+    fn attachments_are_downloaded_into_parent_bundle() {
+        // Given a post with an attached image and an orphan attachment
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[<img src="http://example.com/up/cat.jpg">]]></content:encoded>
+                    <wp:post_id>7</wp:post_id>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>cat</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/blog/post1/cat</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_id>8</wp:post_id>
+                    <wp:post_parent>7</wp:post_parent>
+                    <wp:post_type><![CDATA[attachment]]></wp:post_type>
+                    <wp:status><![CDATA[inherit]]></wp:status>
+                    <wp:attachment_url>http://example.com/up/cat.jpg</wp:attachment_url>
+                </item>
+                <item>
+                    <title>logo</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/logo</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_id>9</wp:post_id>
+                    <wp:post_parent>0</wp:post_parent>
+                    <wp:post_type><![CDATA[attachment]]></wp:post_type>
+                    <wp:status><![CDATA[inherit]]></wp:status>
+                    <wp:attachment_url>http://example.com/up/logo.png</wp:attachment_url>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it into bundles with media
+        let fs = FakeFs::new(input);
+        let options = Options {
+            bundles: true,
+            download_media: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then the image lands in the post's bundle and the link points to it
+        assert_eq!(
+            fs.calls(),
+            &[
+                "fetch(http://example.com/up/cat.jpg)",
+                "create_dir_all(\"site/content/blog/post1\")",
+                "write_file(\"site/content/blog/post1/cat.jpg\")",
+                "fetch(http://example.com/up/logo.png)",
+                "create_dir_all(\"site/static/uploads\")",
+                "write_file(\"site/static/uploads/logo.png\")",
+                "create_dir_all(\"site/content/blog/post1\")",
+                "create_section(\"site/content/_index.md\")",
+                "create_section(\"site/content/blog/_index.md\")",
+                "create_page(\
+                    \"site/content/blog/post1/index.md\", \
+                    Post 1, \
+                    2008-09-01 21:02:27 +00:00, \
+                    ![](cat.jpg)\
+                )",
+            ]
+        );
+    }
+
+    #[test]
+    fn config_records_generator() {
+        // Given a WP export
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>My "Blog"</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with config generation
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            config: true,
+            ..Options::default()
+        };
+        convert("export.xml".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then config.toml tells how it was made
+        let files = fs.into_files();
+        let config = file(&files, "site/config.toml");
+        assert!(config.starts_with(
+            "base_url = \"http://example.com\"\n\
+             title = \"My \\\"Blog\\\"\"\n\
+             \n\
+             [extra]\n"
+        ));
+        assert!(config.contains(&format!(
+            "generator = \"wordpress-to-zola {}\"\n",
+            env!("CARGO_PKG_VERSION")
+        )));
+        assert!(config.contains("generated_at = "));
+        assert!(config.contains("source = \"export.xml\"\n"));
+    }
+
+    #[test]
+    fn channel_description_and_date_go_into_config() {
+        // Given a WP export with a tagline and an export date
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <description>Just "another" blog</description>
+                <pubDate>Tue, 02 Sep 2008 10:00:00 +0000</pubDate>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with config generation
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            config: true,
+            ..Options::default()
+        };
+        convert("export.xml".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then the tagline is the site description and the date is kept
+        let files = fs.into_files();
+        let config = file(&files, "site/config.toml");
+        assert!(config.contains("title = \"Blog\"\ndescription = \"Just \\\"another\\\" blog\"\n"));
+        assert!(config.contains("last_build_date = 2008-09-02T10:00:00+00:00\n"));
+    }
+
+    #[test]
+    fn permalink_guids_become_aliases() {
+        // Given a post whose guid is a permalink different from its link
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/2008/09/post1/</link>
+                    <guid isPermaLink="true">http://example.com/?p=1</guid>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/2008/09/post2/</link>
+                    <guid isPermaLink="false">http://example.com/?p=2</guid>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with aliases
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            aliases: true,
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then both old urls of the first post become aliases
+        let files = fs.into_files();
+        assert!(file(&files, "2008/09/post1.md")
+            .contains("aliases = [\"/2008/09/post1/\", \"/?p=1\"]\n"));
+        assert!(file(&files, "2008/09/post2.md").contains("aliases = [\"/2008/09/post2/\"]\n"));
+
+        // And without trailing slashes when stripping them
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            aliases: true,
+            trailing_slash: TrailingSlash::Strip,
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+        let files = fs.into_files();
+        assert!(file(&files, "2008/09/post1.md")
+            .contains("aliases = [\"/2008/09/post1\", \"/?p=1\"]\n"));
+        assert!(file(&files, "2008/09/post2.md").contains("aliases = [\"/2008/09/post2\"]\n"));
+    }
+
+    #[test]
+    fn max_posts_limits_conversion() {
+        // Given three posts
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/a/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/b/post2</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 3</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/c/post3</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert at most two of them
+        let fs = FakeFs::new(input);
+        let options = Options {
+            max_posts: Some(2),
+            ..Options::default()
+        };
+        convert("".into(), "output".into(), &options, &fs).unwrap();
+
+        // Then only the first two posts and their sections are written
+        assert_eq!(
+            fs.calls(),
+            &[
+                "create_dir_all(\"output/a\")",
+                "create_section(\"output/_index.md\")",
+                "create_section(\"output/a/_index.md\")",
+                "create_page(\"output/a/post1.md\", Post 1, 2008-09-01 21:02:27 +00:00, Hello)",
+                "create_dir_all(\"output/b\")",
+                "create_section(\"output/b/_index.md\")",
+                "create_page(\"output/b/post2.md\", Post 2, 2008-09-01 21:02:27 +00:00, Hello)",
+            ]
+        );
+    }
+
+    #[test]
+    fn section_templates_replace_generated_index() {
+        // Given posts in two sections and a template for one of them
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/news/post2</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+        let template = "+++\ntitle = \"Blog\"\ntemplate = \"blog.html\"\n+++\n";
+        let fs = MemoryFs::new(input).with_file("templates/blog.md", template);
+
+        // When we convert it with the template directory
+        let options = Options {
+            section_template_dir: Some("templates".into()),
+            ..Options::default()
+        };
+        convert("".into(), "content".into(), &options, &fs).unwrap();
+
+        // Then the templated section is used verbatim and the other one is generated
+        let files = fs.into_files();
+        assert_eq!(file(&files, "content/blog/_index.md"), template);
+        assert!(file(&files, "content/news/_index.md").contains("sort_by = \"date\""));
+    }
+
+    #[test]
+    fn external_links_are_listed_once() {
+        // Given two posts linking to the same and to different external sites
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[<a href="https://rust-lang.org/">Rust</a> and <a href="https://getzola.org/">Zola</a>]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post2</link>
+                    <content:encoded><![CDATA[<a href="https://getzola.org/">Zola</a> and <a href="http://example.com/blog/post1">post 1</a>]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it collecting external links
+        let fs = FakeFs::new(input);
+        let options = Options {
+            external_links: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then each external link is listed once and internal links are not
+        assert!(fs.calls().contains(
+            &"create_links(\"site/external-links.txt\", [\"https://rust-lang.org/\", \"https://getzola.org/\"])"
+                .to_owned()
+        ));
+    }
+
+    #[test]
+    fn only_converts_the_post_with_the_slug() {
+        // Given a WP export with several posts
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/a/post1/</link>
+                    <wp:post_name><![CDATA[post1]]></wp:post_name>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/b/post2/</link>
+                    <wp:post_name><![CDATA[post2]]></wp:post_name>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 3</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/c/post3/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert only one of them
+        let fs = FakeFs::new(input);
+        let options = Options {
+            only: Some("post2".to_owned()),
+            ..Options::default()
+        };
+        convert("".into(), "output".into(), &options, &fs).unwrap();
+
+        // Then just that post and its section are written
+        assert_eq!(
+            fs.calls(),
+            &[
+                "create_dir_all(\"output/b\")",
+                "create_section(\"output/_index.md\")",
+                "create_section(\"output/b/_index.md\")",
+                "create_page(\"output/b/post2.md\", Post 2, 2008-09-01 21:02:27 +00:00, Hello)",
+            ]
+        );
+    }
+
+    #[test]
+    fn source_note_is_appended() {
+        // Given a WP export with a post in it
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with a source note
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            source_note: Some(crate::DEFAULT_SOURCE_NOTE.to_owned()),
+            ..Options::default()
+        };
+        convert("".into(), "content".into(), &options, &fs).unwrap();
+
+        // Then the post ends with the note about where it came from
+        let files = fs.into_files();
+        assert!(file(&files, "content/blog/post1.md").ends_with(
+            "Hello\n\n> Originally published on 2008-09-01 at https://example.com/blog/post1/.\n"
+        ));
+    }
+
+    #[test]
+    fn broken_items_are_counted_or_fatal_when_strict() {
+        // Given a WP export with a good post and one with a broken date
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>sometime in 2008</pubDate>
+                    <link>https://example.com/blog/post2</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let summary = convert(
+            "".into(),
+            "output".into(),
+            &Options::default(),
+            &FakeFs::new(input),
+        )
+        .unwrap();
+
+        // Then the good post is converted and the broken one reported
+        assert_eq!(
+            summary,
+            Summary {
+                converted: 1,
+                skipped: 0,
+                errors: 1,
+                warnings: vec![],
+                shortcodes: Default::default(),
+                terms: Default::default(),
+            }
+        );
+        assert!(!summary.is_clean());
+
+        // And in strict mode the broken post stops the conversion
+        let options = Options {
+            strict: true,
+            ..Options::default()
+        };
+        assert!(convert("".into(), "output".into(), &options, &FakeFs::new(input)).is_err());
+    }
+
+    #[test]
+    fn flat_permalinks_go_to_default_section() {
+        // Given a post with a flat permalink
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>My Post</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/my-post/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with a default section
+        let fs = FakeFs::new(input);
+        let options = Options {
+            default_section: "blog".to_owned(),
+            ..Options::default()
+        };
+        convert("".into(), "output".into(), &options, &fs).unwrap();
+
+        // Then the post lands in that section
+        assert_eq!(
+            fs.calls(),
+            &[
+                "create_dir_all(\"output/blog\")",
+                "create_section(\"output/_index.md\")",
+                "create_section(\"output/blog/_index.md\")",
+                "create_page(\"output/blog/my-post.md\", My Post, 2008-09-01 21:02:27 +00:00, Hello)",
+            ]
+        );
+    }
+
+    #[test]
+    fn sections_are_created_for_every_level() {
+        // Given a post two levels deep
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/tech/rust/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = FakeFs::new(input);
+        convert("".into(), "output".into(), &Options::default(), &fs).unwrap();
+
+        // Then both the parent and the child section get `_index.md`
+        assert_eq!(
+            fs.calls(),
+            &[
+                "create_dir_all(\"output/tech/rust\")",
+                "create_section(\"output/_index.md\")",
+                "create_section(\"output/tech/_index.md\")",
+                "create_section(\"output/tech/rust/_index.md\")",
+                "create_page(\"output/tech/rust/post1.md\", Post 1, 2008-09-01 21:02:27 +00:00, Hello)",
+            ]
+        );
+    }
+
+    #[test]
+    fn homepage_section_is_created_once() {
+        // Given a blog with posts in two sections
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <description>Thoughts</description>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/a/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/b/post2</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = MemoryFs::new(input);
+        convert("".into(), "content".into(), &Options::default(), &fs).unwrap();
+
+        // Then there is a single homepage listing recent posts
+        let files = fs.into_files();
+        let homepages = files
+            .iter()
+            .filter(|(path, _)| path.as_path() == std::path::Path::new("content/_index.md"))
+            .count();
+        assert_eq!(homepages, 1);
+        assert_eq!(
+            file(&files, "content/_index.md"),
+            "+++\n\
+             title = \"Blog\"\n\
+             description = \"Thoughts\"\n\
+             sort_by = \"date\"\n\
+             paginate_by = 5\n\
+             +++\n"
+        );
+    }
+
+    #[test]
+    fn statuses_choose_which_posts_convert() {
+        // Given a published and a private post
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Public</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/public</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Secret</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/secret</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[private]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+        let converted = |options: &Options| {
+            let fs = MemoryFs::new(input);
+            convert("".into(), "".into(), options, &fs).unwrap();
+            fs.into_files()
+                .into_iter()
+                .map(|(path, _)| path.to_string_lossy().into_owned())
+                .filter(|path| !path.ends_with("_index.md"))
+                .collect::<Vec<_>>()
+        };
+
+        // When we convert it by default, only the published post is converted
+        assert_eq!(converted(&Options::default()), &["blog/public.md"]);
+
+        // When we ask for private posts too, both are converted
+        let options = Options {
+            statuses: vec!["publish".parse().unwrap(), "private".parse().unwrap()],
+            ..Options::default()
+        };
+        assert_eq!(converted(&options), &["blog/public.md", "blog/secret.md"]);
+
+        // And unknown statuses are reported
+        assert!("published".parse::<Status>().is_err());
+    }
+
+    #[test]
+    fn private_posts_are_marked_private() {
+        // Given a published and a private post
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Public</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/public</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Secret</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/secret</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[private]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert private posts too
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            statuses: vec![Status::Publish, Status::Private],
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then only the private one is flagged for the theme
+        let files = fs.into_files();
+        assert!(!file(&files, "blog/public.md").contains("[extra]"));
+        assert!(
+            file(&files, "blog/secret.md").contains("\n[extra]\nvisibility = \"private\"\n+++\n")
+        );
+    }
+
+    #[test]
+    fn wxr_1_1_exports_are_converted() {
+        // Given an export in the older 1.1 format
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.1/"
+            >
+            <channel>
+                <title>Blog</title>
+                <link>http://example.com</link>
+                <wp:wxr_version>1.1</wp:wxr_version>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <wp:base_blog_url>http://example.com</wp:base_blog_url>
+                <item>
+                    <title>Post 1</title>
+                    <link>http://example.com/blog/post1/</link>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_id>1</wp:post_id>
+                    <wp:post_name>post1</wp:post_name>
+                    <wp:status>publish</wp:status>
+                    <wp:post_type>post</wp:post_type>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let files = convert_str(input).unwrap();
+
+        // Then the post is where its link says
+        assert!(file(&files, "blog/post1.md").ends_with("+++\nHello\n"));
+    }
+
+    #[test]
+    fn post_processing_runs_on_every_post() {
+        // Given a WP export with a post in it
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it shouting
+        let fs = MemoryFs::new(input);
+        convert_with("".into(), "".into(), &Options::default(), &fs, |markdown| {
+            markdown.to_uppercase()
+        })
+        .unwrap();
+
+        // Then the post was post processed
+        assert!(file(&fs.into_files(), "blog/post1.md").ends_with("+++\nHELLO\n"));
+    }
+
+    #[test]
+    fn protected_posts() {
+        // Given a WP export with a password protected post
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Secret</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/secret</link>
+                    <content:encoded><![CDATA[Hidden]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                    <wp:post_password><![CDATA[hunter2]]></wp:post_password>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with default options
+        let fs = MemoryFs::new(input);
+        let summary = convert("".into(), "".into(), &Options::default(), &fs).unwrap();
+
+        // Then the post is skipped
+        assert_eq!(summary.converted, 0);
+        assert!(fs.into_files().is_empty());
+
+        // When we convert it omitting the body
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            protected: Some(Protected::Omit),
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then only the front matter is written and marked as protected
+        assert!(file(&fs.into_files(), "blog/secret.md")
+            .ends_with("\n[extra]\nprotected = true\n+++\n\n"));
+    }
+
+    #[test]
+    fn broken_dates_fall_back_to_post_date_gmt() {
+        // Given a post with a broken pubDate but a good post_date_gmt
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>sometime in 2008</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_date_gmt><![CDATA[2008-09-01 21:02:27]]></wp:post_date_gmt>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = MemoryFs::new(input);
+        let summary = convert("".into(), "".into(), &Options::default(), &fs).unwrap();
+
+        // Then the post is converted and the fallback reported
+        assert_eq!(summary.converted, 1);
+        assert_eq!(
+            summary.warnings,
+            &[Warning::DateFallback {
+                link: "https://example.com/blog/post1".to_owned()
+            }]
+        );
+        assert!(
+            file(&fs.into_files(), "blog/post1.md").contains("date = 2008-09-01T21:02:27+00:00\n")
+        );
+    }
+
+    #[test]
+    fn custom_extension() {
+        // Given a WP export with a post in it
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with the markdown extension
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            extension: "markdown".to_owned(),
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then pages and sections use it
+        let mut paths: Vec<std::path::PathBuf> =
+            fs.into_files().into_iter().map(|(path, _)| path).collect();
+        paths.sort_unstable();
+        assert_eq!(
+            paths,
+            &[
+                std::path::PathBuf::from("_index.markdown"),
+                std::path::PathBuf::from("blog/_index.markdown"),
+                std::path::PathBuf::from("blog/post1.markdown"),
+            ]
+        );
+    }
+
+    #[test]
+    fn sections_can_be_left_alone() {
+        // Given a WP export with a post in it
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it without sections
+        let fs = FakeFs::new(input);
+        let options = Options {
+            sections: false,
+            ..Options::default()
+        };
+        convert("".into(), "output".into(), &options, &fs).unwrap();
+
+        // Then only the directory and the page were created
+        assert_eq!(
+            fs.calls(),
+            &[
+                "create_dir_all(\"output/blog\")",
+                "create_page(\"output/blog/post1.md\", Post 1, 2008-09-01 21:02:27 +00:00, Hello)",
+            ]
+        );
+    }
+
+    #[test]
+    fn categories_use_display_names_as_terms() {
+        // Given a post in a category whose nicename differs from its name
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <category domain="category" nicename="c"><![CDATA[C++]]></category>
+                    <category domain="post_tag" nicename="templates"><![CDATA[Templates]]></category>
+                    <category domain="post_format" nicename="post-format-aside"><![CDATA[Aside]]></category>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with a config
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            config: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+        let files = fs.into_files();
+
+        // Then the page uses display names as terms
+        assert!(file(&files, "site/content/blog/post1.md")
+            .contains("\n[taxonomies]\ncategories = [\"C++\"]\ntags = [\"Templates\"]\n+++\n"));
+        // And the config declares the taxonomies and keeps nicenames
+        let config = file(&files, "site/config.toml");
+        assert!(config.contains("taxonomies = [{ name = \"categories\" }, { name = \"tags\" }]\n"));
+        assert!(config.ends_with(
+            "\n[extra.nicenames.categories]\n\"c\" = \"C++\"\n\n[extra.nicenames.tags]\n\"templates\" = \"Templates\"\n"
+        ));
+    }
+
+    #[test]
+    fn categories_with_descriptions_get_term_pages() {
+        // Given a WP export with described and undescribed categories
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <wp:category>
+                    <wp:term_id>2</wp:term_id>
+                    <wp:category_nicename><![CDATA[c]]></wp:category_nicename>
+                    <wp:category_parent><![CDATA[]]></wp:category_parent>
+                    <wp:cat_name><![CDATA[C++]]></wp:cat_name>
+                    <wp:category_description><![CDATA[Posts about <b>C++</b>.]]></wp:category_description>
+                </wp:category>
+                <wp:category>
+                    <wp:term_id>3</wp:term_id>
+                    <wp:category_nicename><![CDATA[misc]]></wp:category_nicename>
+                    <wp:category_parent><![CDATA[]]></wp:category_parent>
+                    <wp:cat_name><![CDATA[Misc]]></wp:cat_name>
+                </wp:category>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with term pages
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            term_pages: true,
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+        let files = fs.into_files();
+
+        // Then the described category has a page with its description
+        assert_eq!(
+            file(&files, "categories/c/_index.md"),
+            "+++\ntitle = \"C++\"\nsort_by = \"date\"\npaginate_by = 5\n+++\nPosts about **C++**.\n"
+        );
+        // And the other one has none
+        assert!(files
+            .iter()
+            .all(|(path, _)| !path.starts_with("categories/misc")));
+
+        // When we paginate category pages by 10
+        let fs = MemoryFs::new(input);
+        let mut options = Options {
+            term_pages: true,
+            ..Options::default()
+        };
+        options.paginate.insert("categories".to_owned(), 10);
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the term page is paginated by 10 as well
+        assert!(file(&fs.into_files(), "categories/c/_index.md").contains("\npaginate_by = 10\n"));
+    }
+
+    #[test]
+    fn blank_posts_are_skipped_unless_kept() {
+        // Given a post with only a blank paragraph
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[<p>&nbsp;</p>]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = MemoryFs::new(input);
+        let summary = convert("".into(), "".into(), &Options::default(), &fs).unwrap();
+
+        // Then it's skipped as empty
+        let empty = Warning::EmptyContent {
+            link: "https://example.com/blog/post1".to_owned(),
+        };
+        assert_eq!(summary.skipped, 1);
+        assert_eq!(summary.warnings, std::slice::from_ref(&empty));
+        assert!(fs.into_files().is_empty());
+
+        // When we keep empty posts
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            keep_empty: true,
+            ..Options::default()
+        };
+        let summary = convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then it's written without a body
+        assert_eq!(summary.converted, 1);
+        assert_eq!(summary.warnings, &[empty]);
+        assert!(file(&fs.into_files(), "blog/post1.md").ends_with("+++\n\n"));
+    }
+
+    #[test]
+    fn posts_can_be_grouped_by_year() {
+        // Given posts from different years with deep permalinks
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Old</title>
+                    <pubDate>Tue, 01 Jan 2019 10:00:00 +0000</pubDate>
+                    <link>https://example.com/blog/2019/01/old/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>New</title>
+                    <pubDate>Wed, 01 Jan 2020 10:00:00 +0000</pubDate>
+                    <link>https://example.com/blog/2020/01/new/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert them grouped by year
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            group_by: Some(GroupBy::Year),
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then each year is a section with its posts
+        let mut paths: Vec<String> = fs
+            .into_files()
+            .into_iter()
+            .map(|(path, _)| path.to_string_lossy().into_owned())
+            .collect();
+        paths.sort_unstable();
+        assert_eq!(
+            paths,
+            &[
+                "2019/_index.md",
+                "2019/old.md",
+                "2020/_index.md",
+                "2020/new.md",
+                "_index.md"
+            ]
+        );
+    }
+
+    #[test]
+    fn channel_terms_complete_the_taxonomies() {
+        // Given a WP export defining a tag no post uses
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <wp:category>
+                    <wp:term_id>2</wp:term_id>
+                    <wp:category_nicename><![CDATA[c]]></wp:category_nicename>
+                    <wp:category_parent><![CDATA[]]></wp:category_parent>
+                    <wp:cat_name><![CDATA[C++]]></wp:cat_name>
+                </wp:category>
+                <wp:tag>
+                    <wp:term_id>3</wp:term_id>
+                    <wp:tag_slug><![CDATA[rust-lang]]></wp:tag_slug>
+                    <wp:tag_name><![CDATA[Rust]]></wp:tag_name>
+                    <wp:tag_description><![CDATA[Posts about Rust.]]></wp:tag_description>
+                </wp:tag>
+                <wp:term>
+                    <wp:term_id>4</wp:term_id>
+                    <wp:term_taxonomy><![CDATA[nav_menu]]></wp:term_taxonomy>
+                    <wp:term_slug><![CDATA[main]]></wp:term_slug>
+                    <wp:term_name><![CDATA[Main]]></wp:term_name>
+                </wp:term>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <category domain="category" nicename="c"><![CDATA[C++]]></category>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with a config and term pages
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            config: true,
+            term_pages: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+        let files = fs.into_files();
+
+        // Then the config knows every term by its WordPress slug
+        let config = file(&files, "site/config.toml");
+        assert!(config.contains("taxonomies = [{ name = \"categories\" }, { name = \"tags\" }]\n"));
+        assert!(config.ends_with(
+            "\n[extra.nicenames.categories]\n\"c\" = \"C++\"\n\n[extra.nicenames.tags]\n\"rust-lang\" = \"Rust\"\n"
+        ));
+        // And the described tag has a term page
+        assert!(
+            file(&files, "site/content/tags/rust/_index.md").ends_with("+++\nPosts about Rust.\n")
+        );
+    }
+
+    #[test]
+    fn long_slugs_are_cut_to_unique_file_names() {
+        // Given two posts with long slugs which differ only at the end
+        let slug = "a".repeat(300);
+        let input = format!(
+            r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/{slug}-1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/{slug}-2</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#,
+            slug = slug
+        );
+
+        // When we convert them
+        let files = convert_str(&input).unwrap();
+
+        // Then their file names fit the limit and are still different
+        let names: Vec<String> = files
+            .iter()
+            .filter(|(path, _)| path.starts_with("blog") && !path.ends_with("_index.md"))
+            .map(|(path, _)| path.file_name().unwrap().to_string_lossy().into_owned())
+            .collect();
+        assert_eq!(names.len(), 2);
+        assert_ne!(names[0], names[1]);
+        for name in &names {
+            assert!(name.len() <= 255, "{} is too long", name.len());
+            assert!(name.starts_with("aaaa") && name.ends_with(".md"));
+        }
+    }
+
+    #[test]
+    fn drafts_get_a_section_of_their_own() {
+        // Given a published post and a draft
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Public</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/public</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Unfinished</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/unfinished</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[draft]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert drafts into their own section
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            statuses: vec![Status::Publish, Status::Draft],
+            drafts: true,
+            ..Options::default()
+        };
+        convert("".into(), "output".into(), &options, &fs).unwrap();
+        let files = fs.into_files();
+
+        // Then the draft is in `drafts` and marked as one
+        assert!(file(&files, "output/drafts/unfinished.md").contains("\ndraft = true\n"));
+        // And the published post stays where it was
+        assert!(!file(&files, "output/blog/public.md").contains("draft"));
+        assert!(files
+            .iter()
+            .all(|(path, _)| !path.ends_with("drafts/public.md")));
+    }
+
+    #[test]
+    fn manifest_maps_old_urls_to_new_pages() {
+        // Given a WP export with two posts
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/post2/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with a manifest
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            manifest: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then every post has an entry
+        assert_eq!(
+            file(&fs.into_files(), "site/manifest.json"),
+            "{\n  \"https://example.com/blog/post1/\": { \"path\": \"/blog/post1/\", \"slug\": \"post1\" },\n  \
+             \"https://example.com/post2/\": { \"path\": \"/posts/post2/\", \"slug\": \"post2\" }\n}\n"
+        );
+    }
+
+    #[test]
+    fn front_matter_template_is_merged_into_pages() {
+        // Given a post and a front matter template also setting a title
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[private]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+        let template = "# theme defaults\ntemplate = \"post.html\"\ntitle = \"Default\"\n\n\
+                        [extra]\ncomments = true\nvisibility = \"public\"\n";
+
+        // When we convert it with the template
+        let fs = MemoryFs::new(input).with_file("front-matter.toml", template);
+        let options = Options {
+            statuses: vec![Status::Private],
+            front_matter_template: Some("front-matter.toml".into()),
+            ..Options::default()
+        };
+        convert("".into(), "content".into(), &options, &fs).unwrap();
+
+        // Then the template fields are added without replacing computed ones
+        assert_eq!(
+            file(&fs.into_files(), "content/blog/post1.md"),
+            "+++\ntitle = \"Post 1\"\ndate = 2008-09-01T21:02:27+00:00\ntemplate = \"post.html\"\n\n\
+             [extra]\ncomments = true\nvisibility = \"private\"\n+++\nHello\n"
+        );
+    }
+
+    #[test]
+    fn base_path_nests_the_blog_in_the_site() {
+        // Given a post in a section
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/news/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it under a base path
+        let fs = FakeFs::new(input);
+        let options = Options {
+            base_path: Some("blog".into()),
+            manifest: true,
+            ..Options::default()
+        };
+        convert("".into(), "output".into(), &options, &fs).unwrap();
+
+        // Then the post and its sections are under the base path, leaving
+        // the site's own home page alone
+        let calls = fs.calls();
+        assert!(calls.contains(&"create_section(\"output/blog/_index.md\")".to_owned()));
+        assert!(calls.contains(&"create_section(\"output/blog/news/_index.md\")".to_owned()));
+        assert!(!calls
+            .iter()
+            .any(|call| call.starts_with("create_section(\"output/_index.md")));
+        assert!(calls
+            .iter()
+            .any(|call| call.starts_with("create_page(\"output/blog/news/post1.md\"")));
+        assert!(calls
+            .iter()
+            .any(|call| call.contains("path: \"/blog/news/post1/\"")));
+    }
+
+    #[test]
+    fn front_matter_is_the_same_across_runs() {
+        // Given a private, password protected post with extra fields from a template
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <category domain="post_tag" nicename="b"><![CDATA[B]]></category>
+                    <category domain="post_tag" nicename="a"><![CDATA[A]]></category>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[private]]></wp:status>
+                    <wp:post_password><![CDATA[secret]]></wp:post_password>
+                </item>
+            </channel>
+        </rss>
+        "#;
+        let template = "[extra]\nzebra = 1\nauthor = \"me\"\n";
+        let options = Options {
+            statuses: vec![Status::Private],
+            protected: Some(Protected::Keep),
+            front_matter_template: Some("front-matter.toml".into()),
+            ..Options::default()
+        };
+
+        // When we convert it twice
+        let run = || {
+            let fs = MemoryFs::new(input).with_file("front-matter.toml", template);
+            convert("".into(), "content".into(), &options, &fs).unwrap();
+            file(&fs.into_files(), "content/blog/post1.md").to_owned()
+        };
+        let first = run();
+
+        // Then the pages are identical, with `[extra]` sorted by key
+        assert_eq!(first, run());
+        assert!(first.contains(
+            "\n[extra]\nauthor = \"me\"\nprotected = true\nvisibility = \"private\"\nzebra = 1\n+++\n"
+        ));
+    }
+
+    #[test]
+    fn channel_without_items_converts_nothing() {
+        // Given an export without items
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = FakeFs::new(input);
+        let summary = convert("".into(), "output".into(), &Options::default(), &fs).unwrap();
+
+        // Then nothing is written and nothing failed
+        assert!(fs.calls().is_empty());
+        assert_eq!(summary.converted, 0);
+        assert!(summary.is_clean());
+    }
+
+    #[test]
+    fn uploads_are_copied_from_a_local_backup() {
+        // Given a post with an image from the uploads directory
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[<img src="https://example.com/wp-content/uploads/2008/09/cat.jpg"> and <a href="https://example.com/about/">about</a>]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it into bundles with a local uploads directory
+        let fs = FakeFs::new(input);
+        let options = Options {
+            bundles: true,
+            uploads_dir: Some("backup/wp-content/uploads".into()),
+            ..Options::default()
+        };
+        convert("".into(), "output".into(), &options, &fs).unwrap();
+
+        // Then the image is copied into the bundle and linked from there
+        let calls = fs.calls();
+        assert!(
+            calls.contains(&"read_bytes(\"backup/wp-content/uploads/2008/09/cat.jpg\")".to_owned())
+        );
+        assert!(calls.contains(&"write_file(\"output/blog/post1/cat.jpg\")".to_owned()));
+        assert_eq!(
+            calls
+                .iter()
+                .filter(|call| call.starts_with("write_file("))
+                .count(),
+            1
+        );
+        assert!(calls
+            .iter()
+            .any(|call| call.contains("![](cat.jpg) and [about](https://example.com/about/)")));
+    }
+
+    #[test]
+    fn scan_counts_post_types_and_statuses() {
+        // Given an export with a plugin's post type and status
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Snippet 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <content:encoded><![CDATA[<?php ?>]]></content:encoded>
+                    <wp:post_type><![CDATA[wpcode]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Snippet 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <content:encoded><![CDATA[<?php ?>]]></content:encoded>
+                    <wp:post_type><![CDATA[wpcode]]></wp:post_type>
+                    <wp:status><![CDATA[wc-completed]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we scan it
+        let fs = FakeFs::new(input);
+        let scan = super::scan(&["export.xml".into()], &fs).unwrap();
+
+        // Then every type and status is counted and nothing is written
+        assert!(fs.calls().is_empty());
+        assert_eq!(
+            scan.to_string(),
+            "Post types:\n    post: 1\n    wpcode: 2 (unknown)\n\
+             Statuses:\n    publish: 2\n    wc-completed: 1 (unknown)\n"
+        );
+    }
+
+    #[test]
+    fn scheduled_posts_are_converted_only_when_asked() {
+        // Given a published and a scheduled post
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Fri, 01 Sep 2045 09:00:00 +0000</pubDate>
+                    <link>https://example.com/blog/post2/</link>
+                    <content:encoded><![CDATA[Soon]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[future]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it by default
+        let fs = MemoryFs::new(input);
+        convert("".into(), "content".into(), &Options::default(), &fs).unwrap();
+
+        // Then the scheduled post is left out
+        let files = fs.into_files();
+        assert!(!files.iter().any(|(path, _)| path.ends_with("post2.md")));
+
+        // When we ask for scheduled posts too
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            statuses: vec![Status::Publish, Status::Future],
+            ..Options::default()
+        };
+        convert("".into(), "content".into(), &options, &fs).unwrap();
+
+        // Then it keeps its future date and is not a draft
+        assert_eq!(
+            file(&fs.into_files(), "content/blog/post2.md"),
+            "+++\ntitle = \"Post 2\"\ndate = 2045-09-01T09:00:00+00:00\n+++\nSoon\n"
+        );
+    }
+
+    #[test]
+    fn identical_attachments_are_stored_once() {
+        // Given two attachments with the same contents, one linked in a
+        // scaled variant
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[<img src="http://example.com/up/cat-300x200.jpg"> <img src="http://example.com/up/cat-copy.jpg">]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>cat</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/cat</link>
+                    <content:encoded><![CDATA[]]></content:encoded>
+                    <wp:post_type><![CDATA[attachment]]></wp:post_type>
+                    <wp:status><![CDATA[inherit]]></wp:status>
+                    <wp:attachment_url>http://example.com/up/cat.jpg</wp:attachment_url>
+                </item>
+                <item>
+                    <title>cat copy</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/cat-copy</link>
+                    <content:encoded><![CDATA[]]></content:encoded>
+                    <wp:post_type><![CDATA[attachment]]></wp:post_type>
+                    <wp:status><![CDATA[inherit]]></wp:status>
+                    <wp:attachment_url>http://example.com/up/cat-copy.jpg</wp:attachment_url>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with media
+        let fs = FakeFs::new(input);
+        let options = Options {
+            download_media: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then one file is stored and both images point to it
+        let calls = fs.calls();
+        let written: Vec<&String> = calls
+            .iter()
+            .filter(|call| call.starts_with("write_file("))
+            .collect();
+        assert_eq!(written, ["write_file(\"site/static/uploads/cat.jpg\")"]);
+        assert!(calls
+            .iter()
+            .any(|call| call.contains("![](/uploads/cat.jpg) ![](/uploads/cat.jpg)")));
+    }
+
+    #[test]
+    fn posts_with_excluded_content_are_skipped() {
+        // Given the default post and a real one in another section
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Hello world!</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/uncategorized/hello-world/</link>
+                    <content:encoded><![CDATA[Welcome to WordPress. This is your first post. Edit or delete it, then start writing!]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we exclude the default post by its content
+        let fs = FakeFs::new(input);
+        let options = Options {
+            exclude_content: vec![Regex::new("^Welcome to WordPress").unwrap()],
+            ..Options::default()
+        };
+        let summary = convert("".into(), "output".into(), &options, &fs).unwrap();
+
+        // Then only the other post and its sections are written
+        assert_eq!(summary.converted, 1);
+        let calls = fs.calls();
+        assert!(!calls.iter().any(|call| call.contains("uncategorized")));
+        assert!(calls.contains(&"create_section(\"output/blog/_index.md\")".to_owned()));
+    }
+
+    #[test]
+    fn interrupted_writes_leave_no_partial_file() {
+        // Given a directory with an older version of a page
+        let dir = std::env::temp_dir().join(format!("wordpress-to-zola-{}", std::process::id()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let path = dir.join("post.md");
+        std::fs::write(&path, "old").unwrap();
+
+        // When writing the new version fails halfway
+        let result = super::write_atomically(&path, |file| {
+            file.write_all(b"+++\ntitle = ")?;
+            Err(std::io::Error::other("killed"))
+        });
+
+        // Then the old version is untouched and nothing else is left behind
+        assert!(result.is_err());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "old");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+
+        // When writing succeeds
+        super::write_atomically(&path, |file| file.write_all(b"new")).unwrap();
+
+        // Then the page is replaced as a whole
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+        assert_eq!(std::fs::read_dir(&dir).unwrap().count(), 1);
+        std::fs::remove_dir_all(&dir).unwrap();
+    }
+
+    #[test]
+    fn sections_can_be_paginated_differently() {
+        // Given posts in two sections
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/pages/post2/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we paginate one by 10 and turn pagination off for the other
+        let fs = MemoryFs::new(input);
+        let mut options = Options::default();
+        options.paginate.insert("blog".to_owned(), 10);
+        options.paginate.insert("pages".to_owned(), 0);
+        convert("".into(), "content".into(), &options, &fs).unwrap();
+
+        // Then each section has its own pagination and the rest the default
+        let files = fs.into_files();
+        assert!(file(&files, "content/blog/_index.md").contains("\npaginate_by = 10\n"));
+        assert!(!file(&files, "content/pages/_index.md").contains("paginate_by"));
+        assert!(file(&files, "content/_index.md").contains("\npaginate_by = 5\n"));
+    }
+
+    #[test]
+    fn posts_of_all_inputs_are_converted() {
+        // Given an export split into two files with a post each
+        let part = |title: &str| {
+            format!(
+                r#"<?xml version="1.0" encoding="UTF-8" ?>
+                <rss version="2.0"
+                    xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                    xmlns:wp="http://wordpress.org/export/1.2/"
+                >
+                <channel>
+                    <title>Blog</title>
+                    <wp:base_site_url>http://example.com</wp:base_site_url>
+                    <item>
+                        <title>{title}</title>
+                        <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                        <description></description>
+                        <link>http://example.com/work/{title}</link>
+                        <content:encoded><![CDATA[Hello]]></content:encoded>
+                        <wp:post_type><![CDATA[post]]></wp:post_type>
+                        <wp:status><![CDATA[publish]]></wp:status>
+                    </item>
+                </channel>
+            </rss>
+            "#
+            )
+        };
+        let fs = MemoryFs::new("")
+            .with_file("export-1.xml", &part("first"))
+            .with_file("export-2.xml", &part("second"));
+
+        // When we convert both
+        let inputs = ["export-1.xml".into(), "export-2.xml".into()];
+        let summary =
+            convert_all_with(&inputs, "".into(), &Options::default(), &fs, str::to_owned).unwrap();
+
+        // Then the posts of both are written into their section
+        assert_eq!(summary.converted, 2);
+        let files = fs.into_files();
+        let paths: Vec<_> = files
+            .iter()
+            .map(|(path, _)| path.to_str().unwrap())
+            .collect();
+        assert!(paths.contains(&"work/first.md"), "{:?}", paths);
+        assert!(paths.contains(&"work/second.md"), "{:?}", paths);
+    }
+
+    #[test]
+    fn attachment_captions_become_alt_text() {
+        // Given a post showing a scaled image with a caption and one
+        // showing an image without
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:excerpt="http://wordpress.org/export/1.2/excerpt/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[<img src="http://example.com/up/cat-300x200.jpg" alt="cat-300x200" title="Tom">]]></content:encoded>
+                    <excerpt:encoded><![CDATA[]]></excerpt:encoded>
+                    <wp:post_id>7</wp:post_id>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/blog/post2</link>
+                    <content:encoded><![CDATA[<img src="http://example.com/up/dog.jpg" alt="Rex">]]></content:encoded>
+                    <excerpt:encoded><![CDATA[]]></excerpt:encoded>
+                    <wp:post_id>10</wp:post_id>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>cat</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/blog/post1/cat</link>
+                    <content:encoded><![CDATA[A photo taken in 2008]]></content:encoded>
+                    <excerpt:encoded><![CDATA[Tom [the cat] asleep]]></excerpt:encoded>
+                    <wp:post_id>8</wp:post_id>
+                    <wp:post_parent>7</wp:post_parent>
+                    <wp:post_type><![CDATA[attachment]]></wp:post_type>
+                    <wp:status><![CDATA[inherit]]></wp:status>
+                    <wp:attachment_url>http://example.com/up/cat.jpg</wp:attachment_url>
+                </item>
+                <item>
+                    <title>dog</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/blog/post2/dog</link>
+                    <content:encoded><![CDATA[]]></content:encoded>
+                    <excerpt:encoded><![CDATA[]]></excerpt:encoded>
+                    <wp:post_id>9</wp:post_id>
+                    <wp:post_parent>10</wp:post_parent>
+                    <wp:post_type><![CDATA[attachment]]></wp:post_type>
+                    <wp:status><![CDATA[inherit]]></wp:status>
+                    <wp:attachment_url>http://example.com/up/dog.jpg</wp:attachment_url>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it into bundles with media
+        let fs = FakeFs::new(input);
+        let options = Options {
+            bundles: true,
+            download_media: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then the captioned image gets its caption as alt text and the
+        // other one keeps its alt
+        let calls = fs.calls();
+        assert!(calls.contains(
+            &"create_page(\
+                \"site/content/blog/post1/index.md\", \
+                Post 1, \
+                2008-09-01 21:02:27 +00:00, \
+                ![Tom \\[the cat\\] asleep](cat.jpg \"Tom\")\
+            )"
+            .to_owned()
+        ));
+        assert!(calls.contains(
+            &"create_page(\
+                \"site/content/blog/post2/index.md\", \
+                Post 2, \
+                2008-09-01 21:02:27 +00:00, \
+                ![Rex](dog.jpg)\
+            )"
+            .to_owned()
+        ));
+    }
+
+    #[test]
+    fn staggered_dates_follow_original_order() {
+        // Given two posts listed newest first
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>New</title>
+                    <pubDate>Tue, 02 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/work/new</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Old</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/work/old</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it staggered by a week
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            stagger: Some(7),
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the old post comes out first, in the future, and the new
+        // one a week later
+        let files = fs.into_files();
+        let date = |path: &str| {
+            let contents = file(&files, path);
+            let line = contents.lines().find(|line| line.starts_with("date = "));
+            chrono::DateTime::parse_from_rfc3339(&line.unwrap()["date = ".len()..]).unwrap()
+        };
+        let (old, new) = (date("work/old.md"), date("work/new.md"));
+        assert!(old > chrono::Utc::now());
+        assert_eq!(new - old, chrono::Duration::days(7));
+    }
+
+    #[test]
+    fn categories_without_domain_are_tags() {
+        // Given a post with a bare category and a domained one
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <category><![CDATA[Rust Lang]]></category>
+                    <category domain="category" nicename="c"><![CDATA[C++]]></category>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with a config
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            config: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+        let files = fs.into_files();
+
+        // Then the bare one is a tag and the other a category
+        assert!(file(&files, "site/content/blog/post1.md")
+            .contains("\n[taxonomies]\ntags = [\"Rust Lang\"]\ncategories = [\"C++\"]\n+++\n"));
+        assert!(file(&files, "site/config.toml")
+            .ends_with("\n[extra.nicenames.tags]\n\"rust-lang\" = \"Rust Lang\"\n"));
+
+        // And a category when configured so
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            bare_categories: BareCategories::Categories,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+        assert!(file(&fs.into_files(), "site/content/blog/post1.md")
+            .contains("\n[taxonomies]\ncategories = [\"Rust Lang\", \"C++\"]\n+++\n"));
+    }
+
+    #[test]
+    fn deep_links_collapse_into_the_slug() {
+        // Given a post with a dated permalink three levels deep
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Tue, 12 Mar 2019 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/2019/03/12/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with sections one level deep
+        let fs = FakeFs::new(input);
+        let options = Options {
+            max_depth: Some(1),
+            ..Options::default()
+        };
+        convert("".into(), "output".into(), &options, &fs).unwrap();
+
+        // Then the post is in the year's section with the rest in its slug
+        assert_eq!(
+            fs.calls(),
+            &[
+                "create_dir_all(\"output/2019\")",
+                "create_section(\"output/_index.md\")",
+                "create_section(\"output/2019/_index.md\")",
+                "create_page(\
+                    \"output/2019/03-12-post1.md\", \
+                    Post 1, \
+                    2019-03-12 21:02:27 +00:00, \
+                    Hello\
+                )",
+            ]
+        );
+    }
+
+    #[test]
+    fn raw_html_is_written_next_to_the_page() {
+        // Given a post with text looking like zola syntax
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <description></description>
+                    <link>http://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[<p>Hello {{ world }}</p>]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with raw html
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            emit_raw_html: true,
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the transformed html is written beside the markdown
+        let files = fs.into_files();
+        let html = file(&files, "blog/post1.html");
+        assert!(html.contains("<zola-raw>"), "{}", html);
+        assert!(html.contains("Hello"), "{}", html);
+        assert!(file(&files, "blog/post1.md").contains("Hello"));
+    }
+
+    #[test]
+    fn malformed_items_do_not_stop_conversion() {
+        // Given a valid post among items missing title, date, link,
+        // content or post type
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <wp:post_name>untitled</wp:post_name>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Typeless</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/typeless</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                </item>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = FakeFs::new(input);
+        let summary = convert("".into(), "".into(), &Options::default(), &fs).unwrap();
+
+        // Then the valid post is converted and the broken items skipped
+        assert_eq!(summary.converted, 1);
+        assert_eq!(summary.errors, 1);
+        assert_eq!(
+            summary.warnings,
+            [Warning::MissingField {
+                title: "Typeless".to_owned(),
+                field: "wp:post_type"
+            }]
+        );
+        assert!(fs
+            .calls()
+            .iter()
+            .any(|call| call.starts_with("create_page(\"blog/post1.md\"")));
+    }
+
+    #[test]
+    fn sections_are_titled_after_their_category() {
+        // Given posts in a section named after a category and in another
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <wp:category>
+                    <wp:category_nicename><![CDATA[cpp]]></wp:category_nicename>
+                    <wp:cat_name><![CDATA[C++ "Modern"]]></wp:cat_name>
+                </wp:category>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/cpp/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/my-travels/post2</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = MemoryFs::new(input);
+        convert("".into(), "".into(), &Options::default(), &fs).unwrap();
+
+        // Then the first section has the category's display name and the
+        // other one its directory name in title case
+        let files = fs.into_files();
+        assert!(file(&files, "cpp/_index.md").contains("title = \"C++ \\\"Modern\\\"\"\n"));
+        assert!(file(&files, "my-travels/_index.md").contains("title = \"My Travels\"\n"));
+    }
+
+    #[test]
+    fn markdown_content_is_passed_through() {
+        // Given a post written in markdown
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Call *snake_case* names
+
+* like `my_var`]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it passing markdown through
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            passthrough_markdown: true,
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the content is written without escapes
+        assert!(file(&fs.into_files(), "blog/post1.md")
+            .ends_with("+++\nCall *snake_case* names\n\n* like `my_var`\n"));
+    }
+
+    #[test]
+    fn unhandled_shortcodes_are_tallied() {
+        // Given posts with shortcodes nothing converts
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Write me [contact-form id="1"] or see [gallery]]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post2</link>
+                    <content:encoded><![CDATA[[contact-form] and a [link](http://example.com)]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let summary = convert(
+            "".into(),
+            "".into(),
+            &Options::default(),
+            &MemoryFs::new(input),
+        )
+        .unwrap();
+
+        // Then every use of a shortcode is counted, most used first
+        assert_eq!(
+            summary.shortcode_tally().as_deref(),
+            Some("contact-form (2), gallery (1)")
+        );
+    }
+
+    #[test]
+    fn naive_dates_take_the_assumed_timezone() {
+        // Given a post with only a naive local post_date
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_date><![CDATA[2008-09-02 00:02:27]]></wp:post_date>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it assuming the blog is at +03:00
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            assume_tz: Some("+03:00".parse().unwrap()),
+            ..Options::default()
+        };
+        let summary = convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the date keeps its local time with that offset
+        assert_eq!(summary.converted, 1);
+        assert!(
+            file(&fs.into_files(), "blog/post1.md").contains("date = 2008-09-02T00:02:27+03:00\n")
+        );
+    }
+
+    /// Export with a post WordPress shows on three pages.
+    const PAGINATED_POST: &str = r#"<?xml version="1.0" encoding="UTF-8" ?>
+        <rss version="2.0"
+            xmlns:content="http://purl.org/rss/1.0/modules/content/"
+            xmlns:wp="http://wordpress.org/export/1.2/"
+        >
+        <channel>
+            <title>Blog</title>
+            <wp:base_site_url>http://example.com</wp:base_site_url>
+            <item>
+                <title>Post 1</title>
+                <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                <link>http://example.com/blog/post1</link>
+                <content:encoded><![CDATA[<p>One</p>
+<!--nextpage-->
+<p>Two</p>
+<!--nextpage-->
+<p>Three</p>]]></content:encoded>
+                <wp:post_type><![CDATA[post]]></wp:post_type>
+                <wp:status><![CDATA[publish]]></wp:status>
+            </item>
+        </channel>
+    </rss>
+    "#;
+
+    #[test]
+    fn next_page_markers_are_stripped() {
+        // Given a post with two nextpage markers
+
+        // When we convert it stripping them
+        let fs = MemoryFs::new(PAGINATED_POST);
+        let options = Options {
+            next_page: Some(NextPage::Strip),
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then it is one page with the text of all of them
+        assert!(file(&fs.into_files(), "blog/post1.md").ends_with("+++\nOne\n\nTwo\n\nThree\n"));
+    }
+
+    #[test]
+    fn next_page_markers_split_the_post() {
+        // Given a post with two nextpage markers
+
+        // When we convert it splitting it
+        let fs = FakeFs::new(PAGINATED_POST);
+        let options = Options {
+            next_page: Some(NextPage::Split),
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then every part is a page of the post's bundle
+        let pages: Vec<String> = fs
+            .calls()
+            .into_iter()
+            .filter(|call| call.starts_with("create_page"))
+            .collect();
+        assert_eq!(
+            pages,
+            [
+                "create_page(\"blog/post1/2.md\", Post 1 (2), 2008-09-01 21:02:27 +00:00, Two)",
+                "create_page(\"blog/post1/3.md\", Post 1 (3), 2008-09-01 21:02:27 +00:00, Three)",
+                "create_page(\"blog/post1/index.md\", Post 1, 2008-09-01 21:02:27 +00:00, One)",
+            ]
+        );
+    }
+
+    #[test]
+    fn co_authors_are_listed_in_extra() {
+        // Given a post with two co-authors and one with a single creator
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:dc="http://purl.org/dc/elements/1.1/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post1</link>
+                    <dc:creator><![CDATA[admin]]></dc:creator>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <category domain="author" nicename="cap-alice"><![CDATA[alice]]></category>
+                    <category domain="author" nicename="cap-bob"><![CDATA[bob]]></category>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post2</link>
+                    <dc:creator><![CDATA[admin]]></dc:creator>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let fs = MemoryFs::new(input);
+        convert("".into(), "".into(), &Options::default(), &fs).unwrap();
+
+        // Then co-authors are an array and a single author is a string
+        let files = fs.into_files();
+        assert!(
+            file(&files, "blog/post1.md").contains("\n[extra]\nauthors = [\"alice\", \"bob\"]\n")
+        );
+        assert!(file(&files, "blog/post2.md").contains("\n[extra]\nauthor = \"admin\"\n"));
+    }
+
+    #[test]
+    fn root_relative_urls_are_internal() {
+        // Given a post with a root-relative image and link, and an external
+        // link ending in the same path
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[<img src="/up/cat-300x200.jpg"> <a href="/about/">about</a> <a href="https://mirror.org/up/cat.jpg">mirror</a>]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>cat</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/cat</link>
+                    <wp:post_id>8</wp:post_id>
+                    <wp:post_type><![CDATA[attachment]]></wp:post_type>
+                    <wp:status><![CDATA[inherit]]></wp:status>
+                    <wp:attachment_url>http://example.com/up/cat.jpg</wp:attachment_url>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with media, collecting external links
+        let fs = FakeFs::new(input);
+        let options = Options {
+            download_media: true,
+            external_links: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then the image is localized, and only the other site's link is
+        // external and left alone
+        let calls = fs.calls();
+        assert!(calls.iter().any(|call| call.contains(
+            "![](/uploads/cat.jpg) [about](/about/) [mirror](https://mirror.org/up/cat.jpg)"
+        )));
+        assert!(calls.contains(
+            &"create_links(\"site/external-links.txt\", [\"https://mirror.org/up/cat.jpg\"])"
+                .to_owned()
+        ));
+    }
+
+    #[test]
+    fn empty_titles_come_from_slugs() {
+        // Given posts with an empty title, one also without a slug
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title></title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/my-first-post/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_name>my-first_post</wp:post_name>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title> </title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/?p=5</link>
+                    <content:encoded><![CDATA[World]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it titling posts after their slugs
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            title_from_slug: true,
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the titles are the slugs in title case, or Untitled
+        let files = fs.into_files();
+        assert!(file(&files, "blog/my-first-post.md").contains("title = \"My First Post\""));
+        assert!(files
+            .iter()
+            .any(|(_, contents)| contents.contains("title = \"Untitled\"")));
+    }
+
+    #[test]
+    fn csv_index_lists_pages() {
+        // Given a tagged post with a comma and quotes in its title
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:dc="http://purl.org/dc/elements/1.1/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Hello, "world"</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/hello/</link>
+                    <dc:creator><![CDATA[admin]]></dc:creator>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <category domain="post_tag" nicename="rust"><![CDATA[Rust]]></category>
+                    <category domain="post_tag" nicename="zola"><![CDATA[Zola]]></category>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with a csv index
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            csv_index: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then the page is listed under a header with its title quoted
+        assert_eq!(
+            file(&fs.into_files(), "site/posts.csv"),
+            "title,date,slug,path,author,tags\n\
+             \"Hello, \"\"world\"\"\",2008-09-01T21:02:27+00:00,hello,/blog/hello/,admin,Rust;Zola\n"
+        );
+    }
+
+    #[test]
+    fn sections_can_be_opaque() {
+        // Given a WP export with a post in a section
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it without transparent sections
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            transparent_sections: false,
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the section's front matter has no transparent line
+        assert_eq!(
+            file(&fs.into_files(), "blog/_index.md"),
+            "+++\ntitle = \"Blog\"\nsort_by = \"date\"\npaginate_by = 5\n+++\n"
+        );
+    }
+
+    #[test]
+    fn multisite_blogs_get_directories_of_their_own() {
+        // Given a multisite export with posts of two blogs and of the main site
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Network</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Pie</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/cooking/2008/pie/</link>
+                    <content:encoded><![CDATA[Apples]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Paris</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://travel.example.com/blog/paris/</link>
+                    <content:encoded><![CDATA[Tower]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>News</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/news/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with both blogs
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            blogs: vec![
+                "https://example.com/cooking/".into(),
+                "http://travel.example.com".into(),
+            ],
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the posts of each blog are in its directory, with their
+        // paths relative to the blog
+        let files = fs.into_files();
+        assert!(file(&files, "cooking/2008/pie.md").ends_with("+++\nApples\n"));
+        assert!(file(&files, "travel/blog/paris.md").ends_with("+++\nTower\n"));
+        assert!(file(&files, "blog/news.md").ends_with("+++\nHello\n"));
+        assert!(file(&files, "cooking/_index.md").contains("title = \"Cooking\""));
+    }
+
+    #[test]
+    fn original_terms_are_kept_without_taxonomies() {
+        // Given a post with categories and a tag
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <category domain="category" nicename="cpp"><![CDATA[C++]]></category>
+                    <category domain="category" nicename="rust"><![CDATA[Rust]]></category>
+                    <category domain="post_tag" nicename="templates"><![CDATA[Templates]]></category>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it keeping the original terms without taxonomies
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            taxonomies: false,
+            original_terms: true,
+            config: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then the terms are in extra only, and no taxonomy is declared
+        let files = fs.into_files();
+        assert!(file(&files, "site/content/blog/post1.md").ends_with(
+            "\n[extra]\noriginal_categories = [\"C++\", \"Rust\"]\noriginal_tags = [\"Templates\"]\n+++\nHello\n"
+        ));
+        assert!(!file(&files, "site/config.toml").contains("taxonomies"));
+    }
+
+    #[test]
+    fn check_reports_unknown_post_types() {
+        // Given an export with a plugin's post type
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Snippet 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <content:encoded><![CDATA[<?php ?>]]></content:encoded>
+                    <wp:post_type><![CDATA[wpcode]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we check it
+        let fs = FakeFs::new(input);
+        let report = super::check(&["export.xml".into()], &fs).unwrap();
+
+        // Then it parses, the plugin's type is reported and nothing is written
+        assert!(fs.calls().is_empty());
+        assert_eq!(report, "Parsed 2 items\nUnknown post types: wpcode (1)\n");
+
+        // And a broken export fails the check
+        let fs = FakeFs::new("<rss><channel>");
+        assert!(super::check(&["export.xml".into()], &fs).is_err());
+    }
+
+    #[test]
+    fn front_matter_strings_are_escaped() {
+        // Given titles, terms and a blog description with quotes,
+        // backslashes and control characters
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>C:\Blog</title>
+                <description>"Tabs"	and more</description>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Say "hi" \o/</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/hi</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <category domain="post_tag" nicename="a-b"><![CDATA[a\b]]></category>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it with a config
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            config: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then every string is a valid toml basic string
+        let files = fs.into_files();
+        let page = file(&files, "site/content/blog/hi.md");
+        assert!(page.starts_with("+++\ntitle = \"Say \\\"hi\\\" \\\\o/\"\n"));
+        assert!(page.contains("\n[taxonomies]\ntags = [\"a\\\\b\"]\n"));
+        let config = file(&files, "site/config.toml");
+        assert!(config
+            .contains("title = \"C:\\\\Blog\"\ndescription = \"\\\"Tabs\\\"\\u0009and more\"\n"));
+        assert!(config.contains("\n[extra.nicenames.tags]\n\"a-b\" = \"a\\\\b\"\n"));
+    }
+
+    #[test]
+    fn date_can_come_from_post_date_gmt() {
+        // Given a post with all three dates
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Tue, 02 Sep 2008 00:02:27 +0300</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_date><![CDATA[2008-09-02 00:02:27]]></wp:post_date>
+                    <wp:post_date_gmt><![CDATA[2008-09-01 21:02:27]]></wp:post_date_gmt>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it taking dates from post_date_gmt
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            date_field: DateField::PostDateGmt,
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the date is the GMT one
+        assert!(
+            file(&fs.into_files(), "blog/post1.md").contains("date = 2008-09-01T21:02:27+00:00\n")
+        );
+    }
+
+    #[test]
+    fn aliases_can_be_limited_to_moved_pages() {
+        // Given a post staying where it was and one nested too deep
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/2008/09/post2/</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it into bundles two levels deep with aliases
+        // only for moved pages
+        let fs = MemoryFs::new(input);
+        let options = Options {
+            aliases: true,
+            only_changed_aliases: true,
+            bundles: true,
+            max_depth: Some(2),
+            ..Options::default()
+        };
+        convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then only the moved post gets its old url as an alias
+        let files = fs.into_files();
+        assert!(!file(&files, "blog/post1/index.md").contains("aliases"));
+        assert!(file(&files, "blog/2008/09-post2/index.md")
+            .contains("aliases = [\"/blog/2008/09/post2/\"]\n"));
+    }
+
+    #[test]
+    fn terms_are_reported_with_their_post_counts() {
+        // Given two posts sharing a tag, one with a near duplicate
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <category domain="category" nicename="code"><![CDATA[Code]]></category>
+                    <category domain="post_tag" nicename="javascript"><![CDATA[JavaScript]]></category>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post2</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <category domain="post_tag" nicename="javascript"><![CDATA[JavaScript]]></category>
+                    <category domain="post_tag" nicename="javascript-2"><![CDATA[Javascript]]></category>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let summary = convert(
+            "".into(),
+            "".into(),
+            &Options::default(),
+            &MemoryFs::new(input),
+        )
+        .unwrap();
+
+        // Then the shared tag is counted twice and listed first
+        assert_eq!(
+            summary.term_report(),
+            "categories:\n    Code: 1\ntags:\n    JavaScript: 2\n    Javascript: 1\n"
+        );
+    }
+
+    #[test]
+    fn items_sharing_a_link_are_told_apart() {
+        // Given items which a broken plugin gave the same link
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/?p=0</link>
+                    <guid isPermaLink="false">https://example.com/?p=1</guid>
+                    <content:encoded><![CDATA[One]]></content:encoded>
+                    <wp:post_name><![CDATA[first-post]]></wp:post_name>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 2</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/?p=0</link>
+                    <guid isPermaLink="false">https://example.com/?p=2</guid>
+                    <content:encoded><![CDATA[Two]]></content:encoded>
+                    <wp:post_name><![CDATA[second-post]]></wp:post_name>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 3</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/?p=0</link>
+                    <guid isPermaLink="false">https://example.com/?p=3</guid>
+                    <content:encoded><![CDATA[Three]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>Post 4</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/?p=0</link>
+                    <guid isPermaLink="false">https://example.com/?p=4</guid>
+                    <content:encoded><![CDATA[Four]]></content:encoded>
+                    <wp:post_name><![CDATA[second-post]]></wp:post_name>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it
+        let files = convert_str(input).unwrap();
+
+        // Then every item takes its post_name, or its guid, as slug,
+        // numbered if another item has it already
+        let body = |path: &str| {
+            file(&files, path)
+                .rsplit("+++\n")
+                .next()
+                .unwrap_or_default()
+        };
+        assert_eq!(body("blog/first-post.md"), "One\n");
+        assert_eq!(body("blog/second-post.md"), "Two\n");
+        assert_eq!(body("blog/p-3.md"), "Three\n");
+        assert_eq!(body("blog/second-post-2.md"), "Four\n");
+        assert!(!files
+            .iter()
+            .any(|(path, _)| path.to_string_lossy().contains('?')));
+    }
+
+    #[test]
+    fn max_posts_counts_split_posts_once() {
+        // Given a post shown on three pages followed by two more posts
+        let more = r#"<item>
+                <title>Post 2</title>
+                <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                <link>http://example.com/blog/post2</link>
+                <content:encoded><![CDATA[Hello]]></content:encoded>
+                <wp:post_type><![CDATA[post]]></wp:post_type>
+                <wp:status><![CDATA[publish]]></wp:status>
+            </item>
+            <item>
+                <title>Post 3</title>
+                <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                <link>http://example.com/blog/post3</link>
+                <content:encoded><![CDATA[Hello]]></content:encoded>
+                <wp:post_type><![CDATA[post]]></wp:post_type>
+                <wp:status><![CDATA[publish]]></wp:status>
+            </item>
+        </channel>"#;
+        let input = PAGINATED_POST.replace("</channel>", more);
+
+        // When we split pages and convert at most two posts
+        let fs = FakeFs::new(&input);
+        let options = Options {
+            next_page: Some(NextPage::Split),
+            bundles: true,
+            max_posts: Some(2),
+            ..Options::default()
+        };
+        let summary = convert("".into(), "".into(), &options, &fs).unwrap();
+
+        // Then the split post and the next one are written and counted,
+        // the third is not
+        assert_eq!(summary.converted, 2);
+        let pages: Vec<String> = fs
+            .calls()
+            .into_iter()
+            .filter(|call| call.starts_with("create_page"))
+            .collect();
+        assert_eq!(
+            pages,
+            [
+                "create_page(\"blog/post1/2.md\", Post 1 (2), 2008-09-01 21:02:27 +00:00, Two)",
+                "create_page(\"blog/post1/3.md\", Post 1 (3), 2008-09-01 21:02:27 +00:00, Three)",
+                "create_page(\"blog/post1/index.md\", Post 1, 2008-09-01 21:02:27 +00:00, One)",
+                "create_page(\"blog/post2/index.md\", Post 2, 2008-09-01 21:02:27 +00:00, Hello)",
+            ]
+        );
+    }
+
+    #[test]
+    fn different_uploads_with_the_same_name_are_kept_apart() {
+        // Given a post with two different images named alike
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1/</link>
+                    <content:encoded><![CDATA[<img src="https://example.com/wp-content/uploads/2019/01/image.jpg"> <img src="https://example.com/wp-content/uploads/2020/05/image.jpg">]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we copy them from a local uploads directory
+        let fs = MemoryFs::new(input)
+            .with_file("uploads/2019/01/image.jpg", "old")
+            .with_file("uploads/2020/05/image.jpg", "new");
+        let options = Options {
+            uploads_dir: Some("uploads".into()),
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then both are stored under their own names and linked from there
+        let files = fs.into_files();
+        assert_eq!(file(&files, "site/static/uploads/image.jpg"), "old");
+        assert_eq!(file(&files, "site/static/uploads/image-2.jpg"), "new");
+        assert!(file(&files, "site/content/blog/post1.md")
+            .contains("![](/uploads/image.jpg) ![](/uploads/image-2.jpg)"));
+    }
+
+    #[test]
+    fn attachment_captions_become_alt_text_of_sized_images() {
+        // Given a post showing a sized image with a caption, twice
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:excerpt="http://wordpress.org/export/1.2/excerpt/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>http://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[<img class="size-medium" src="http://example.com/up/cat-300x200.jpg" alt="cat-300x200" width="300" height="200"> <img src="http://example.com/up/cat.jpg" width="600">]]></content:encoded>
+                    <wp:post_id>7</wp:post_id>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+                <item>
+                    <title>cat</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>http://example.com/blog/post1/cat</link>
+                    <content:encoded><![CDATA[]]></content:encoded>
+                    <excerpt:encoded><![CDATA[Tom "the cat" asleep]]></excerpt:encoded>
+                    <wp:post_id>8</wp:post_id>
+                    <wp:post_parent>7</wp:post_parent>
+                    <wp:post_type><![CDATA[attachment]]></wp:post_type>
+                    <wp:status><![CDATA[inherit]]></wp:status>
+                    <wp:attachment_url>http://example.com/up/cat.jpg</wp:attachment_url>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it into bundles with media
+        let fs = FakeFs::new(input);
+        let options = Options {
+            bundles: true,
+            download_media: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+
+        // Then both images get the caption as alt attribute
+        let calls = fs.calls();
+        assert!(calls.contains(
+            &"create_page(\
+                \"site/content/blog/post1/index.md\", \
+                Post 1, \
+                2008-09-01 21:02:27 +00:00, \
+                <img class=\"size-medium\" src=\"cat.jpg\" alt=\"Tom &quot;the cat&quot; asleep\" width=\"300\" height=\"200\"> \
+                <img alt=\"Tom &quot;the cat&quot; asleep\" src=\"cat.jpg\" width=\"600\">\
+            )"
+            .to_owned()
+        ));
+    }
+}
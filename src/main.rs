@@ -1,460 +1,450 @@
-//! # wordpress-to-zola
-//! Wordress to Zola converter.
-//!
-//! ## What & Why?
-//!
-//! This is a small tool for generating sections and pages for
-//! [zola][] from wordress XML.  If you want to move your blog from
-//! wordress to zola, this tool will do that for you.
-//!
-//! ## How do I use it?
-//!
-//! First you should go to your wordpress's `/wp-admin/export.php` and
-//! download XML file.  Then you run `cargo run -- input.xml` and it
-//! will produce a `content` directory will all the pages and
-//! sections.
-//!
-//! ## How does it work?
-//!
-//! TODO: document
-//! TODO: generate config.toml?
-//!
-//! ## Debugging
-//! One may want to set logging level to debug to see more details.
-//! ```
-//! export RUST_LOG=wordpress_to_zola=debug
-//! cargo run
-//! ```
-//!
-//! [zola][https://www.getzola.org/]
-
-mod transform_html;
-
-use chrono::{DateTime, FixedOffset};
-use html2md::parse_html;
 use log::*;
-use serde::Deserialize;
-use serde_xml_rs::from_reader;
-use std::collections::HashSet;
+use regex::Regex;
 use std::env::args;
-use std::fs::create_dir_all;
-use std::fs::File;
-use std::io::{Read, Result, Write};
+use std::fs::{read_to_string, File};
 use std::path::{Path, PathBuf};
-use transform_html::transform_html;
-
-/// Paginate section by this number of posts.
-/// TODO: make configurable
-const PAGINATE_BY: usize = 5;
-
-fn main() -> Result<()> {
-    env_logger::init();
-
-    if let [input, output] = args().skip(1).take(2).collect::<Vec<_>>().as_slice() {
-        let fs = RealFs {};
-
-        convert(input.into(), output.into(), &fs)?;
-    } else {
-        eprintln!("Usage: wordpress-to-zola ./input.xml ./output-dir");
-    }
-    Ok(())
+use std::process::exit;
+use wordpress_to_zola::{
+    check, convert_all_with, scan, Options, RealFs, Summary, ZipFs, DEFAULT_SOURCE_NOTE,
+};
+
+const USAGE: &str = "Usage: wordpress-to-zola [options] ./input.xml... ./output-dir
+       wordpress-to-zola --report-only-unknown ./input.xml...
+       wordpress-to-zola --check ./input.xml...
+
+Options:
+    --input-list FILE       also read the exports listed in FILE, one path per line, # for comments
+    --default-section NAME  section for posts with flat or no links (default: posts)
+    --max-depth N           nest sections at most N levels deep, joining deeper link parts into the slug with -
+    --weights               number posts newest first and sort sections by weight
+    --stagger N             date posts N days apart from tomorrow on, oldest first, to publish them gradually
+    --keep-link-attributes  keep links with rel or target attributes as html
+    --keep-tags LIST        keep these comma separated elements as html, e.g. details,summary,kbd,mark
+    --keep-colors           keep colored text as html spans instead of dropping the color
+    --strip-shortcodes      remove leftover wordpress shortcodes like [contact-form]
+    --extension EXT         extension of generated files (default: md)
+    --plain-spaces          turn non-breaking spaces into regular ones outside code
+    --definition-lists STYLE
+                            write <dl> terms and definitions as colon or dash lines (default: colon)
+    --group-by year         put posts into a section per year instead of following their links
+    --base-path DIR         put all pages and sections into DIR under the output directory
+    --blog URL              url of a blog of a multisite export, converted into a directory named
+                            after it, e.g. https://example.com/travel into travel/ (repeatable)
+    --bundles               write posts as page bundles: post/index.md
+    --download-media        download attachments next to their posts or into static/uploads
+    --youtube-shortcode NAME
+    --vimeo-shortcode NAME
+    --quote-shortcode NAME  write youtube and vimeo embeds and pullquotes with the theme's shortcode NAME
+                            (defaults: youtube, vimeo, pullquote)
+    --media-shortcodes      write audio and video as audio(sources=[...]) and video(...) shortcodes
+    --uploads-dir DIR       copy linked uploads from the wp-content/uploads DIR of a backup instead
+    --passthrough-markdown  write post content as is, for blogs written in markdown
+    --emit-raw-html         write the html each page was converted from next to it, for review
+    --config                generate config.toml in the parent of the output directory
+    --aliases               keep old wordpress urls working with aliases
+    --only-changed-aliases  keep old wordpress urls working with aliases where the page moved
+    --trailing-slash POLICY keep or strip the trailing slash of aliases and manifest paths (default: keep)
+    --append-source-note    end posts with a note about their original date and url
+    --source-note TEXT      note to append instead, with {date} and {url} placeholders
+    --nextpage MODE         strip <!--nextpage--> markers or split posts at them into post/index.md, post/2.md...
+    --assume-tz OFFSET      timezone like +03:00 of wordpress post dates, used for posts with a broken pubDate
+    --date-field FIELD      take page dates from pubDate, post_date or post_date_gmt (default: pubDate)
+    --title-case CASE       normalize titles to lower, title or sentence case
+    --title-from-slug       title posts without a title after their slug, or Untitled
+    --status LIST           convert posts with these comma separated statuses (default: publish),
+                            e.g. publish,future for scheduled posts zola publishes once their date passes
+    --protected MODE        convert password protected posts, omitting or keeping the body (omit or keep)
+    --drafts                put drafts into a drafts section, use with --status publish,draft
+    --only SLUG             convert only the post with this slug
+    --exclude-content-matching REGEX
+                            skip posts whose converted content matches REGEX (repeatable)
+    --keep-empty            convert posts without content instead of skipping them
+    --max-posts N           stop after converting N posts
+    --strict                fail on the first post that cannot be converted
+    --report-only-unknown   list post types and statuses with their counts, marking unknown ones, and write nothing
+    --term-report           print categories and tags with their post counts, most used first
+    --check                 only parse the exports, list unknown post types and statuses, and fail if they don't parse
+    --quiet                 only print errors
+    --verbose               print what happens to every item
+    --partial-exit-code N   exit with N when some items were skipped or failed (default: 0)
+    --replace FROM=TO       replace text in every post, e.g. an old image host (repeatable)
+    --zip FILE              write everything into a zip archive instead of the output directory
+    --wrap N                hard-wrap paragraphs at N columns
+    --external-links        list outbound links in external-links.txt next to the output directory
+    --bare-categories TAXONOMY
+                            treat <category> elements without a domain as tags, categories or ignore them (default: tags)
+    --term-pages            write <taxonomy>/<term>/_index.md for categories and tags with a description
+    --no-taxonomies         don't put categories and tags into zola taxonomies
+    --original-terms        keep categories and tags as original_categories and original_tags in [extra]
+    --no-sections           don't write section _index.md files
+    --no-transparent        don't make sections transparent, for themes listing their pages twice
+    --paginate SECTION=N    show N posts per page in SECTION, like blog or blog/news, / for the home page,
+                            categories/rust or categories for term pages and 0 for no pagination
+                            (repeatable, default: 5)
+    --front-matter-template FILE
+                            add the toml fields in FILE to every page, keeping title, date and other computed ones
+    --manifest              map old urls to new pages in manifest.json next to the output directory
+    --csv-index             list title, date, slug, path, author and tags of pages in posts.csv next to the output directory
+    --section-template-dir DIR
+                            use DIR/<section>.md as the section's _index.md when present";
+
+/// Parsed command line.
+struct Args {
+    /// Exports merged before conversion.
+    inputs: Vec<PathBuf>,
+    output: PathBuf,
+    options: Options,
+    /// Exit code when some items were skipped or failed.
+    partial_exit_code: i32,
+    /// Archive to write the output directory into.
+    zip: Option<PathBuf>,
+    /// Text to replace in the markdown of every post.
+    replacements: Vec<(String, String)>,
+    /// Log level overriding `RUST_LOG`.
+    log_level: Option<LevelFilter>,
+    /// Only list what the export contains.
+    report: bool,
+    /// Only parse the export.
+    check: bool,
+    /// Print the terms of every taxonomy with their post counts.
+    term_report: bool,
 }
 
-/// Read xml from `input_file` and create `zola` content directory in
-/// `output_dir`.
-fn convert(input_file: PathBuf, output_dir: PathBuf, fs: &impl Fs) -> Result<()> {
-    let file = fs.open(&input_file)?;
-    let rss: Rss = from_reader(file).expect("cannot parse xml");
-
-    // We want to strip `base_url` from posts url later on to get a
-    // nice filename for a post.
-    let base_url = rss.channel.base_site_url;
-
-    // We will make `_index.md` for every top level section we will
-    // find. This set is used to only do that once per section.
-    let mut sections = HashSet::new();
-
-    for item in rss.channel.item {
-        match item.status {
-            Status::Publish => {} // take only published posts
-            _ => continue,        // skip everything else
+fn main() {
+    let args = match parse_args(args().skip(1)) {
+        Ok(args) => args,
+        Err(error) => {
+            eprintln!("{}\n\n{}", error, USAGE);
+            exit(2);
         }
-        match item.post_type {
-            PostType::Post => {
-                let path = output_dir.join(generate_path(&base_url, &item.link));
-                info!("Post [{:?}] {} -> {:?}", item.status, item.title, &path);
-
-                let section = path.parent().expect("no parent in filename");
-                // ensure all directories are in place
-                debug!("Creating directory {:?}", section);
-                fs.create_dir_all(&path.parent().expect("no parent in filename"))?;
+    };
 
-                // if it's the first time we see this section, create section file
-                if sections.insert(section.to_owned()) {
-                    fs.create_section(section)?;
-                }
-
-                let date =
-                    DateTime::parse_from_rfc2822(&item.pub_date).expect("cannot parse pubDate");
-
-                let html = transform_html(item.content());
-                let markdown = parse_html(&html);
-
-                fs.create_page(&path, &item.title.replace('"', "\\\""), date, &markdown)?;
+    let mut logger = env_logger::Builder::from_default_env();
+    if let Some(level) = args.log_level {
+        logger.filter_level(level);
+    }
+    logger.init();
+
+    if args.check {
+        match check(&args.inputs, &RealFs {}) {
+            Ok(report) => print!("{}", report),
+            Err(error) => {
+                eprintln!("{}", error);
+                exit(1);
             }
-            PostType::Attachment => debug!("Ignoring attachment {}", item.title),
-            _ => debug!("Ignoring unknown post type {}", item.title),
         }
+        return;
     }
-    Ok(())
-}
-
-/// Top level wrapper
-#[derive(Debug, Deserialize)]
-struct Rss {
-    channel: Channel,
-}
-
-/// Main wrapper
-#[derive(Debug, Deserialize)]
-struct Channel {
-    base_site_url: String,
-    item: Vec<Item>,
-}
-
-/// Item can be either Post or Attachment
-#[derive(Debug, Deserialize)]
-struct Item {
-    title: String,
-    link: String,
-    #[serde(rename = "pubDate")]
-    pub_date: String,
-    post_type: PostType,
-    encoded: Vec<String>,
-    status: Status,
-}
 
-impl Item {
-    /// Helper method to workaround serde-xml inability to work with
-    /// fields containing colons.
-    ///
-    /// See https://github.com/RReverser/serde-xml-rs/issues/64
-    fn content(&self) -> &str {
-        &self.encoded[0]
+    if args.report {
+        match scan(&args.inputs, &RealFs {}) {
+            Ok(scan) => print!("{}", scan),
+            Err(error) => {
+                eprintln!("{}", error);
+                exit(1);
+            }
+        }
+        return;
     }
-}
-
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "lowercase")]
-enum PostType {
-    Attachment,
-    Post,
-    #[serde(other)]
-    Other,
-}
 
-#[derive(Debug, Deserialize)]
-#[serde(rename_all = "lowercase")]
-enum Status {
-    Publish,
-    Draft,
-    Inherit,
-    Private,
+    let result = run(&args);
+    if let (true, Ok(summary)) = (args.term_report, &result) {
+        print!("{}", summary.term_report());
+    }
+    match result {
+        Ok(summary) if summary.is_clean() => {
+            info!("Converted {} posts", summary.converted);
+        }
+        Ok(summary) => {
+            warn!(
+                "Converted {} posts, skipped {} items, {} failed",
+                summary.converted, summary.skipped, summary.errors
+            );
+            exit(args.partial_exit_code);
+        }
+        Err(error) => {
+            eprintln!("{}", error);
+            exit(1);
+        }
+    }
 }
 
-trait Fs {
-    fn open(&self, path: &PathBuf) -> Result<impl Read>;
-
-    fn create_dir_all<P>(&self, path: P) -> Result<()>
-    where
-        P: AsRef<Path>;
-
-    fn create_page(
-        &self,
-        path: &Path,
-        title: &str,
-        date: DateTime<FixedOffset>,
-        markdown: &str,
-    ) -> Result<()>;
-
-    fn create_section(&self, section: &Path) -> Result<()>;
+/// Convert into the output directory or into a zip archive.
+fn run(args: &Args) -> std::io::Result<Summary> {
+    let output = args.output.clone();
+    let post_process = |markdown: &str| {
+        args.replacements
+            .iter()
+            .fold(markdown.to_owned(), |markdown, (from, to)| {
+                markdown.replace(from, to)
+            })
+    };
+    match &args.zip {
+        Some(zip) => {
+            let mut fs = ZipFs::new("", File::create(zip)?);
+            for input in &args.inputs {
+                fs = fs.with_input(input, &read_to_string(input)?);
+            }
+            let summary = convert_all_with(&args.inputs, output, &args.options, &fs, post_process)?;
+            fs.finish()?;
+            Ok(summary)
+        }
+        None => convert_all_with(
+            &args.inputs,
+            output,
+            &args.options,
+            &RealFs {},
+            post_process,
+        ),
+    }
 }
 
-struct RealFs {}
-
-impl Fs for RealFs {
-    fn open(&self, path: &PathBuf) -> Result<impl Read> {
-        File::open(path)
+/// Parse command line into input files, output directory and options.
+fn parse_args(mut args: impl Iterator<Item = String>) -> std::result::Result<Args, String> {
+    let mut options = Options::default();
+    let mut partial_exit_code = 0;
+    let mut zip = None;
+    let mut statuses = Vec::new();
+    let mut replacements = Vec::new();
+    let mut log_level = None;
+    let mut report = false;
+    let mut check = false;
+    let mut term_report = false;
+    let mut positional = Vec::new();
+    let mut inputs = Vec::new();
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing value for {}", arg))
+        };
+        match arg.as_str() {
+            "--input-list" => {
+                let list = PathBuf::from(value()?);
+                let contents = read_to_string(&list)
+                    .map_err(|error| format!("Cannot read {}: {}", list.display(), error))?;
+                inputs.extend(input_list(&contents, &list));
+            }
+            "--default-section" => options.default_section = value()?,
+            "--max-depth" => options.max_depth = Some(number(&value()?)?),
+            "--weights" => options.weights = true,
+            "--stagger" => {
+                options.stagger = Some(
+                    value()?
+                        .parse()
+                        .map_err(|_| "Expected a number of days".to_owned())?,
+                )
+            }
+            "--keep-link-attributes" => options.keep_link_attributes = true,
+            "--keep-colors" => options.keep_colors = true,
+            "--keep-tags" => {
+                for tag in value()?.split(',') {
+                    options.keep_tags.push(tag.trim().to_lowercase());
+                }
+            }
+            "--strip-shortcodes" => options.strip_shortcodes = true,
+            "--extension" => options.extension = value()?,
+            "--plain-spaces" => options.plain_spaces = true,
+            "--definition-lists" => options.definition_lists = value()?.parse()?,
+            "--group-by" => options.group_by = Some(value()?.parse()?),
+            "--base-path" => options.base_path = Some(value()?.into()),
+            "--blog" => options.blogs.push(value()?),
+            "--bundles" => options.bundles = true,
+            "--download-media" => options.download_media = true,
+            "--youtube-shortcode" => options.shortcodes.youtube = value()?,
+            "--vimeo-shortcode" => options.shortcodes.vimeo = value()?,
+            "--quote-shortcode" => options.shortcodes.pullquote = value()?,
+            "--media-shortcodes" => options.media_shortcodes = true,
+            "--uploads-dir" => options.uploads_dir = Some(value()?.into()),
+            "--passthrough-markdown" => options.passthrough_markdown = true,
+            "--emit-raw-html" => options.emit_raw_html = true,
+            "--config" => options.config = true,
+            "--aliases" => options.aliases = true,
+            "--only-changed-aliases" => {
+                options.aliases = true;
+                options.only_changed_aliases = true;
+            }
+            "--trailing-slash" => options.trailing_slash = value()?.parse()?,
+            "--append-source-note" => options.source_note = Some(DEFAULT_SOURCE_NOTE.to_owned()),
+            "--source-note" => options.source_note = Some(value()?),
+            "--nextpage" => options.next_page = Some(value()?.parse()?),
+            "--assume-tz" => {
+                let offset = value()?;
+                options.assume_tz = Some(
+                    offset
+                        .parse()
+                        .map_err(|_| format!("Expected an offset like +03:00, got {}", offset))?,
+                )
+            }
+            "--date-field" => options.date_field = value()?.parse()?,
+            "--title-case" => options.title_case = Some(value()?.parse()?),
+            "--title-from-slug" => options.title_from_slug = true,
+            "--status" => {
+                for status in value()?.split(',') {
+                    statuses.push(status.trim().parse()?);
+                }
+            }
+            "--protected" => options.protected = Some(value()?.parse()?),
+            "--drafts" => options.drafts = true,
+            "--only" => options.only = Some(value()?),
+            "--exclude-content-matching" => {
+                let pattern = value()?;
+                let regex = Regex::new(&pattern)
+                    .map_err(|error| format!("Invalid regex {}: {}", pattern, error))?;
+                options.exclude_content.push(regex);
+            }
+            "--keep-empty" => options.keep_empty = true,
+            "--max-posts" => options.max_posts = Some(number(&value()?)?),
+            "--strict" => options.strict = true,
+            "--report-only-unknown" => report = true,
+            "--check" => check = true,
+            "--term-report" => term_report = true,
+            "--quiet" => log_level = Some(LevelFilter::Error),
+            "--verbose" => log_level = Some(LevelFilter::Debug),
+            "--zip" => zip = Some(value()?.into()),
+            "--replace" => {
+                let replacement = value()?;
+                let (from, to) = replacement
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected FROM=TO, got {}", replacement))?;
+                replacements.push((from.to_owned(), to.to_owned()));
+            }
+            "--partial-exit-code" => {
+                partial_exit_code = value()?
+                    .parse()
+                    .map_err(|_| "Expected an exit code".to_owned())?
+            }
+            "--manifest" => options.manifest = true,
+            "--csv-index" => options.csv_index = true,
+            "--external-links" => options.external_links = true,
+            "--wrap" => options.wrap = Some(number(&value()?)?),
+            "--bare-categories" => options.bare_categories = value()?.parse()?,
+            "--term-pages" => options.term_pages = true,
+            "--no-taxonomies" => options.taxonomies = false,
+            "--original-terms" => options.original_terms = true,
+            "--no-sections" => options.sections = false,
+            "--no-transparent" => options.transparent_sections = false,
+            "--paginate" => {
+                let pagination = value()?;
+                let (section, count) = pagination
+                    .split_once('=')
+                    .ok_or_else(|| format!("Expected SECTION=N, got {}", pagination))?;
+                options
+                    .paginate
+                    .insert(section.trim_matches('/').to_owned(), number(count)?);
+            }
+            "--front-matter-template" => options.front_matter_template = Some(value()?.into()),
+            "--section-template-dir" => options.section_template_dir = Some(value()?.into()),
+            flag if flag.starts_with("--") => return Err(format!("Unknown option {}", flag)),
+            _ => positional.push(arg),
+        }
     }
 
-    fn create_dir_all<P>(&self, path: P) -> Result<()>
-    where
-        P: AsRef<Path>,
-    {
-        create_dir_all(path)
+    if !statuses.is_empty() {
+        options.statuses = statuses;
     }
 
-    /// Create post file
-    fn create_page(
-        &self,
-        path: &Path,
-        title: &str,
-        date: DateTime<FixedOffset>,
-        markdown: &str,
-    ) -> Result<()> {
-        let mut file = File::create(path)?;
-        // write front-matter
-        writeln!(file, "+++")?;
-        writeln!(file, "title = \"{}\"", title)?;
-        writeln!(file, "date = {}", date.to_rfc3339())?;
-        writeln!(file, "+++")?;
-        // and content
-        writeln!(file, "{}", markdown)?;
-        Ok(())
+    // the last argument is the output directory, unless only reporting
+    let output = match report || check {
+        true => PathBuf::new(),
+        false => positional.pop().map(PathBuf::from).unwrap_or_default(),
+    };
+    inputs.splice(0..0, positional.into_iter().map(PathBuf::from));
+    if inputs.is_empty() || (!report && !check && output.as_os_str().is_empty()) {
+        return Err("Expected input files and output directory".to_owned());
     }
+    Ok(Args {
+        inputs,
+        output,
+        options,
+        partial_exit_code,
+        zip,
+        replacements,
+        log_level,
+        report,
+        check,
+        term_report,
+    })
+}
 
-    /// Create section `_index.md` file.
-    fn create_section(&self, section: &Path) -> Result<()> {
-        let mut file = File::create(section.join("_index.md"))?;
-        writeln!(file, "+++")?;
-        writeln!(file, "transparent = true")?; // show pages from this section in index.html
-        writeln!(file, "sort_by = \"date\"")?;
-        writeln!(file, "paginate_by = {}", PAGINATE_BY)?;
-        writeln!(file, "+++")?;
-        Ok(())
-    }
+/// Paths in an input list, relative ones resolved against its directory.
+fn input_list(contents: &str, list: &Path) -> Vec<PathBuf> {
+    let dir = list.parent().unwrap_or(Path::new(""));
+    contents
+        .lines()
+        .map(str::trim)
+        .filter(|line| !line.is_empty() && !line.starts_with('#'))
+        .map(|line| dir.join(line))
+        .collect()
 }
 
-/// Generate path for an item by splicing base url from the link.
-fn generate_path(base_url: &str, link: &str) -> PathBuf {
-    PathBuf::from(format!(
-        "{}.md",
-        link.trim_start_matches(&base_url).trim_matches('/')
-    ))
+fn number(value: &str) -> std::result::Result<usize, String> {
+    value
+        .parse()
+        .map_err(|_| format!("Expected a number, got {}", value))
 }
 
 #[cfg(test)]
 mod tests {
-    use std::cell::RefCell;
-
-    use crate::{convert, Fs};
-
-    struct FakeFs {
-        input: String,
-        calls: RefCell<Vec<String>>,
-    }
-
-    impl FakeFs {
-        fn new(input: &str) -> Self {
-            Self {
-                input: input.to_owned(),
-                calls: RefCell::new(Vec::new()),
-            }
-        }
-
-        fn calls(&self) -> Vec<String> {
-            self.calls.borrow().clone()
-        }
-    }
-
-    impl Fs for FakeFs {
-        fn open(&self, _path: &std::path::PathBuf) -> std::io::Result<impl std::io::Read> {
-            Ok(self.input.as_bytes())
-        }
-
-        fn create_dir_all<P>(&self, path: P) -> std::io::Result<()>
-        where
-            P: AsRef<std::path::Path>,
-        {
-            self.calls
-                .borrow_mut()
-                .push(format!("create_dir_all({:?})", path.as_ref()));
-            Ok(())
-        }
-
-        fn create_page(
-            &self,
-            path: &std::path::Path,
-            title: &str,
-            date: chrono::DateTime<chrono::FixedOffset>,
-            markdown: &str,
-        ) -> std::io::Result<()> {
-            self.calls.borrow_mut().push(format!(
-                "create_page({:?}, {}, {}, {})",
-                path, title, date, markdown
-            ));
-            Ok(())
-        }
-
-        fn create_section(&self, section: &std::path::Path) -> std::io::Result<()> {
-            self.calls
-                .borrow_mut()
-                .push(format!("create_section({:?})", section));
-            Ok(())
-        }
+    use super::{input_list, parse_args};
+    use log::LevelFilter;
+    use std::path::{Path, PathBuf};
+
+    fn log_level(flags: &[&str]) -> Option<LevelFilter> {
+        let args = flags
+            .iter()
+            .chain(&["in.xml", "out"])
+            .map(|arg| arg.to_string());
+        parse_args(args).unwrap().log_level
     }
 
     #[test]
-    fn normal_posts_are_converted() {
-        // Given a WP export with a post in it
-        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
-            <rss version="2.0"
-                xmlns:content="http://purl.org/rss/1.0/modules/content/"
-                xmlns:wp="http://wordpress.org/export/1.2/"
-            >
-            <channel>
-                <title>Blog</title>
-                <wp:base_site_url>https://example.com</wp:base_site_url>
-                <item>
-                    <title>Post 1</title>
-                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
-                    <description></description>
-                    <link>http://example.com/post1</link>
-                    <content:encoded><![CDATA[]]></content:encoded>
-                    <wp:post_type><![CDATA[post]]></wp:post_type>
-                    <wp:status><![CDATA[publish]]></wp:status>
-                </item>
-            </channel>
-        </rss>
-        "#;
-
-        // When we convert it
-        let fs = FakeFs::new(input);
-        convert("".into(), "output".into(), &fs).unwrap();
-
-        // Then we create a post and section
+    fn flags_set_the_log_level() {
+        assert_eq!(log_level(&[]), None);
+        assert_eq!(log_level(&["--quiet"]), Some(LevelFilter::Error));
+        assert_eq!(log_level(&["--verbose"]), Some(LevelFilter::Debug));
         assert_eq!(
-            fs.calls(),
-            &[
-                "create_dir_all(\"output/http://example.com\")",
-                "create_section(\"output/http://example.com\")",
-                "create_page(\
-                    \"output/http://example.com/post1.md\", \
-                    Post 1, \
-                    2008-09-01 21:02:27 +00:00, \
-                )",
-            ]
+            log_level(&["--verbose", "--quiet"]),
+            Some(LevelFilter::Error)
         );
     }
 
     #[test]
-    fn unknown_post_types_are_ignored() {
-        // Given a blog item wpcode post_tyoe
-        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
-            <rss version="2.0"
-                xmlns:content="http://purl.org/rss/1.0/modules/content/"
-                xmlns:wp="http://wordpress.org/export/1.2/"
-            >
-            <channel>
-                <title>Blog</title>
-                <wp:base_site_url>https://example.com</wp:base_site_url>
-                <item>
-                    <title>Post 1</title>
-                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
-                    <description></description>
-                    <link>http://example.com/post1</link>
-                    <content:encoded><![CDATA[]]></content:encoded>
-                    <wp:post_type><![CDATA[wpcode]]></wp:post_type>
-                    <wp:status><![CDATA[publish]]></wp:status>
-                </item>
-            </channel>
-        </rss>
-        "#;
+    fn input_list_skips_blank_lines_and_comments() {
+        // Given a list of two exports next to each other
+        let contents =
+            "# split by the exporter\nexport-1.xml\n\n  export-2.xml  \n/tmp/export-3.xml\n";
 
-        // When we convert it
-        let fs = FakeFs::new(input);
-        convert("".into(), "output".into(), &fs).unwrap();
-
-        // Then nothing was generated
-        assert!(fs.calls().is_empty());
-    }
+        // When reading it
+        let inputs = input_list(contents, Path::new("exports/paths.txt"));
 
-    #[test]
-    fn quotes_in_titles_are_escaped() {
-        // Given a blog item with quotes in its title
-        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
-            <rss version="2.0"
-                xmlns:content="http://purl.org/rss/1.0/modules/content/"
-                xmlns:wp="http://wordpress.org/export/1.2/"
-            >
-            <channel>
-                <title>Blog</title>
-                <wp:base_site_url>https://example.com</wp:base_site_url>
-                <item>
-                    <title>Post "1"</title>
-                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
-                    <description></description>
-                    <link>http://example.com/post1</link>
-                    <content:encoded><![CDATA[]]></content:encoded>
-                    <wp:post_type><![CDATA[post]]></wp:post_type>
-                    <wp:status><![CDATA[publish]]></wp:status>
-                </item>
-            </channel>
-        </rss>
-        "#;
-
-        // When we convert it
-        let fs = FakeFs::new(input);
-        convert("".into(), "output".into(), &fs).unwrap();
-
-        // Then the created post escapes the quotes in the title
+        // Then relative paths are resolved against the list's directory
         assert_eq!(
-            fs.calls(),
-            &[
-                "create_dir_all(\"output/http://example.com\")",
-                "create_section(\"output/http://example.com\")",
-                "create_page(\
-                    \"output/http://example.com/post1.md\", \
-                    Post \\\"1\\\", \
-                    2008-09-01 21:02:27 +00:00, \
-                )",
+            inputs,
+            [
+                PathBuf::from("exports/export-1.xml"),
+                PathBuf::from("exports/export-2.xml"),
+                PathBuf::from("/tmp/export-3.xml"),
             ]
         );
     }
 
     #[test]
-    fn paragraphs_are_separated() {
-        // Given a blog item with two paragraphs
-        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
-            <rss version="2.0"
-                xmlns:content="http://purl.org/rss/1.0/modules/content/"
-                xmlns:wp="http://wordpress.org/export/1.2/"
-            >
-            <channel>
-                <title>Blog</title>
-                <wp:base_site_url>https://example.com</wp:base_site_url>
-                <item>
-                    <title>Post "1"</title>
-                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
-                    <description></description>
-                    <link>http://example.com/post1</link>
-                    <content:encoded><![CDATA[para a
-
-para b]]></content:encoded>
-                    <wp:post_type><![CDATA[post]]></wp:post_type>
-                    <wp:status><![CDATA[publish]]></wp:status>
-                </item>
-            </channel>
-        </rss>
-        "#;
-
-        // When we convert it
-        let fs = FakeFs::new(input);
-        convert("".into(), "output".into(), &fs).unwrap();
-
-        // Then the created post contains separate paragraphs
+    fn last_argument_is_the_output_directory() {
+        let args = ["a.xml", "b.xml", "out"].iter().map(|arg| arg.to_string());
+        let args = parse_args(args).unwrap();
         assert_eq!(
-            fs.calls(),
-            &[
-                "create_dir_all(\"output/http://example.com\")",
-                "create_section(\"output/http://example.com\")",
-                "create_page(\
-                    \"output/http://example.com/post1.md\", \
-                    Post \\\"1\\\", \
-                    2008-09-01 21:02:27 +00:00, \
-                    para a\n\npara b\
-                )",
-            ]
+            args.inputs,
+            [PathBuf::from("a.xml"), PathBuf::from("b.xml")]
         );
+        assert_eq!(args.output, PathBuf::from("out"));
+    }
+
+    #[test]
+    fn check_needs_no_output_directory() {
+        let args = ["--check", "a.xml"].iter().map(|arg| arg.to_string());
+        let args = parse_args(args).unwrap();
+        assert!(args.check);
+        assert_eq!(args.inputs, [PathBuf::from("a.xml")]);
     }
 }
@@ -0,0 +1,284 @@
+//! Post-processing of markdown produced by `html2md`.
+
+use regex::Regex;
+use std::sync::LazyLock;
+
+/// Inline code: `` `code` `` or ``` ``co`de`` ```.
+static CODE_SPAN: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`+[^`]*?`+").unwrap());
+
+/// Apply `f` to the parts of `markdown` outside of code blocks and
+/// code spans, leaving code untouched.
+pub fn map_text(markdown: &str, mut f: impl FnMut(&str) -> String) -> String {
+    let mut result = String::with_capacity(markdown.len());
+    let mut text = String::new();
+    let mut in_code_block = false;
+
+    let mut flush = |text: &mut String, result: &mut String| {
+        let mut last = 0;
+        for span in CODE_SPAN.find_iter(text) {
+            result.push_str(&f(&text[last..span.start()]));
+            result.push_str(span.as_str());
+            last = span.end();
+        }
+        result.push_str(&f(&text[last..]));
+        text.clear();
+    };
+
+    for line in markdown.split_inclusive('\n') {
+        let fence = line.trim_start().starts_with("```") || line.trim_start().starts_with("~~~");
+        if in_code_block || fence {
+            flush(&mut text, &mut result);
+            result.push_str(line);
+            if fence {
+                in_code_block = !in_code_block;
+            }
+        } else {
+            text.push_str(line);
+        }
+    }
+    flush(&mut text, &mut result);
+    result
+}
+
+/// Remove WordPress shortcodes like `[contact-form]` or
+/// `[x id="1"]...[/x]` keeping the text they wrap. Returns the
+/// cleaned markdown and the names of removed shortcodes.
+pub fn strip_shortcodes(markdown: &str) -> (String, Vec<String>) {
+    static SHORTCODE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"\[(/?)([A-Za-z][\w\\-]*)(\s[^\[\]\n]*)?\]").unwrap());
+
+    let mut removed = Vec::new();
+    let markdown = map_text(markdown, |text| {
+        let mut result = String::with_capacity(text.len());
+        let mut last = 0;
+        for captures in SHORTCODE.captures_iter(text) {
+            let token = captures.get(0).unwrap();
+            let before = text[..token.start()].chars().last();
+            let after = text[token.end()..].chars().next();
+            // `[text](url)`, `[text][ref]`, `[ref]: url` and escaped brackets are markdown
+            let is_markdown = matches!(before, Some(']') | Some('\\'))
+                || matches!(after, Some('(') | Some('[') | Some(':'));
+            if is_markdown {
+                continue;
+            }
+            result.push_str(&text[last..token.start()]);
+            last = token.end();
+            if captures[1].is_empty() {
+                removed.push(captures[2].replace('\\', ""));
+            }
+        }
+        result.push_str(&text[last..]);
+        result
+    });
+    (markdown, removed)
+}
+
+/// Names of the WordPress shortcodes left in `markdown`, once per use.
+pub fn shortcodes(markdown: &str) -> Vec<String> {
+    strip_shortcodes(markdown).1
+}
+
+/// Replace non-breaking spaces left by `&nbsp;` with regular ones,
+/// collapsing runs of spaces around them. Code keeps them.
+pub fn plain_spaces(markdown: &str) -> String {
+    static NBSP: LazyLock<Regex> = LazyLock::new(|| Regex::new(r" *\u{a0}[ \u{a0}]*").unwrap());
+
+    map_text(markdown, |text| NBSP.replace_all(text, " ").into_owned())
+}
+
+/// Absolute `http(s)` and root-relative urls of links, images, raw html
+/// and shortcode arguments in `markdown` in order of appearance, ignoring
+/// code.
+pub fn links(markdown: &str) -> Vec<String> {
+    static LINK: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"\]\((https?://[^)\s]+|/[^/)\s][^)\s]*)|<(https?://[^>\s]+)>|"(https?://[^"\s]+|/[^/"\s][^"\s]*)""#)
+            .unwrap()
+    });
+
+    let mut links = Vec::new();
+    map_text(markdown, |text| {
+        for captures in LINK.captures_iter(text) {
+            let url = (1..=3).find_map(|i| captures.get(i)).unwrap();
+            links.push(url.as_str().to_owned());
+        }
+        String::new()
+    });
+    links
+}
+
+/// Hard-wrap paragraph text at `width` columns. Code blocks, tables,
+/// headings and raw html are left alone, and code spans and links are
+/// never broken.
+pub fn wrap(markdown: &str, width: usize) -> String {
+    static UNBREAKABLE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"`+[^`]*?`+|!?\[[^\]]*\]\([^)]*\)").unwrap());
+    static PREFIX: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^( *(?:> ?)*)((?:[*+-]|\d+[.)]) +)?").unwrap());
+    // a line starting with `-` or `1.` would become a list
+    static BLOCK_START: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^([*+>#-]|\d+[.)])$").unwrap());
+
+    let mut result = String::with_capacity(markdown.len());
+    let mut in_code_block = false;
+    for line in markdown.split_inclusive('\n') {
+        let trimmed = line.trim_start();
+        let fence = trimmed.starts_with("```") || trimmed.starts_with("~~~");
+        if fence {
+            in_code_block = !in_code_block;
+        }
+        let keep = in_code_block
+            || fence
+            || line.starts_with("    ")
+            || line.starts_with('\t')
+            || ["#", "|", "<"]
+                .iter()
+                .any(|start| trimmed.starts_with(start));
+        if keep {
+            result.push_str(line);
+            continue;
+        }
+
+        let (content, newline) = match line.strip_suffix('\n') {
+            Some(content) => (content, "\n"),
+            None => (line, ""),
+        };
+        // two trailing spaces are a hard line break
+        let body = content.trim_end();
+        let trailing = &content[body.len()..];
+
+        let captures = PREFIX.captures(body).unwrap();
+        let first_prefix = captures.get(0).unwrap().as_str();
+        let quote = captures.get(1).unwrap().as_str();
+        let marker = captures.get(2).map_or("", |marker| marker.as_str());
+        let next_prefix = format!("{}{}", quote, " ".repeat(marker.chars().count()));
+
+        let text = &body[first_prefix.len()..];
+        let protected: Vec<(usize, usize)> = UNBREAKABLE
+            .find_iter(text)
+            .map(|found| (found.start(), found.end()))
+            .collect();
+        let mut words = Vec::new();
+        let mut start = 0;
+        for (i, c) in text.char_indices() {
+            let inside = protected.iter().any(|&(from, to)| from < i && i < to);
+            if c == ' ' && !inside {
+                if start < i {
+                    words.push(&text[start..i]);
+                }
+                start = i + 1;
+            }
+        }
+        if start < text.len() {
+            words.push(&text[start..]);
+        }
+
+        let mut current = first_prefix.to_owned();
+        let mut current_width = current.chars().count();
+        let mut empty = true;
+        for word in words {
+            let word_width = word.chars().count();
+            let starts_block = BLOCK_START.is_match(word);
+            if !empty && current_width + 1 + word_width > width && !starts_block {
+                result.push_str(&current);
+                result.push('\n');
+                current = next_prefix.clone();
+                current_width = current.chars().count();
+                empty = true;
+            }
+            if !empty {
+                current.push(' ');
+                current_width += 1;
+            }
+            current.push_str(word);
+            current_width += word_width;
+            empty = false;
+        }
+        result.push_str(&current);
+        result.push_str(trailing);
+        result.push_str(newline);
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::{links, plain_spaces, strip_shortcodes, wrap};
+
+    #[test]
+    fn self_closing_shortcodes_are_stripped() {
+        assert_eq!(
+            strip_shortcodes("Write me:\n\n[contact-form id=\"3\"]\n\nThanks"),
+            (
+                "Write me:\n\n\n\nThanks".to_owned(),
+                vec!["contact-form".to_owned()]
+            )
+        );
+    }
+
+    #[test]
+    fn paired_shortcodes_keep_their_content() {
+        assert_eq!(
+            strip_shortcodes("a [rev\\_slider alias=\"home\"]b[/rev\\_slider] c"),
+            ("a b c".to_owned(), vec!["rev_slider".to_owned()])
+        );
+    }
+
+    #[test]
+    fn markdown_brackets_are_not_shortcodes() {
+        let markdown = "[link](http://a.b) [ref][x] text[^1]\n\n[x]: http://a.b\n\n[^1]: note";
+        assert_eq!(strip_shortcodes(markdown), (markdown.to_owned(), vec![]));
+    }
+
+    #[test]
+    fn code_is_left_alone() {
+        let markdown = "`[gallery]`\n\n```\n[gallery]\n```\n";
+        assert_eq!(strip_shortcodes(markdown), (markdown.to_owned(), vec![]));
+    }
+
+    #[test]
+    fn non_breaking_spaces_become_plain_outside_code() {
+        assert_eq!(
+            plain_spaces("a\u{a0}\u{a0}b c \u{a0}d `a\u{a0}\u{a0}b`"),
+            "a b c d `a\u{a0}\u{a0}b`"
+        );
+    }
+
+    #[test]
+    fn links_are_found_outside_code() {
+        let markdown = "[a](https://a.com/x) ![b](http://b.com/y.png) <https://c.com>\n\n\
+                        <iframe src=\"https://d.com/embed\"></iframe>\n\n`[e](https://e.com)`\n\n\
+                        {{ video(sources=[\"https://f.com/v.mp4\"]) }}\n\n\
+                        [g](/about/) ![h](/up/h.png) [i](//i.com/x) <img src=\"/up/j.png\">\n";
+        assert_eq!(
+            links(markdown),
+            &[
+                "https://a.com/x",
+                "http://b.com/y.png",
+                "https://c.com",
+                "https://d.com/embed",
+                "https://f.com/v.mp4",
+                "/about/",
+                "/up/h.png",
+                "/up/j.png"
+            ]
+        );
+    }
+
+    #[test]
+    fn long_paragraphs_are_wrapped() {
+        let markdown = "one two three four five six seven\n\n* item one two three four\n";
+        assert_eq!(
+            wrap(markdown, 15),
+            "one two three\nfour five six\nseven\n\n* item one two\n  three four\n"
+        );
+    }
+
+    #[test]
+    fn wrapping_leaves_code_and_links_whole() {
+        let markdown = "see `a b c d` and [the link text](http://a.b)\n\n```\nlong code line that stays\n```\n";
+        assert_eq!(
+            wrap(markdown, 10),
+            "see\n`a b c d`\nand\n[the link text](http://a.b)\n\n```\nlong code line that stays\n```\n"
+        );
+    }
+}
@@ -0,0 +1,825 @@
+//! Custom `html2md` tag handlers for elements it doesn't convert the
+//! way we want.
+
+use crate::{DefinitionList, Options};
+use html2md::{
+    parse_html_custom, Handle, NodeData, StructuredPrinter, TagHandler, TagHandlerFactory,
+};
+use log::*;
+use std::collections::HashMap;
+
+/// Marks blank lines in `<zola-raw>` text, as html2md merges runs of
+/// them, while they matter in code.
+pub const BLANK_LINE: char = '\u{e000}';
+
+/// Convert `html` to markdown using our tag handlers.
+pub fn html_to_markdown(html: &str, options: &Options) -> String {
+    let mut handlers: HashMap<String, Box<dyn TagHandlerFactory>> = HashMap::new();
+    // markdown has no embeds, but zola passes raw html through
+    for tag in &["iframe", "audio", "video"] {
+        handlers.insert(tag.to_string(), Box::new(RawBlockFactory));
+    }
+    if options.media_shortcodes {
+        for tag in &["audio", "video"] {
+            handlers.insert(tag.to_string(), Box::new(MediaShortcodeFactory));
+        }
+    }
+    // html2md indents nested lists by marker width and separates items
+    // after a nested list with blank lines
+    for tag in &["ul", "ol", "menu"] {
+        handlers.insert(tag.to_string(), Box::new(ListFactory));
+    }
+    handlers.insert("li".to_owned(), Box::new(ListItemFactory));
+    // html2md leaves empty quoted lines around every paragraph
+    handlers.insert("blockquote".to_owned(), Box::new(BlockquoteFactory));
+    // markdown has no definition lists
+    handlers.insert("dl".to_owned(), Box::new(ListFactory));
+    for tag in &["dt", "dd"] {
+        handlers.insert(
+            tag.to_string(),
+            Box::new(DefinitionFactory(options.definition_lists)),
+        );
+    }
+    // zola shortcodes must not be escaped
+    handlers.insert("zola-raw".to_owned(), Box::new(RawTextFactory));
+    if options.keep_link_attributes {
+        handlers.insert("a".to_owned(), Box::new(LinkFactory));
+    }
+    // html2md breaks on brackets in alt text and quotes in titles
+    handlers.insert("img".to_owned(), Box::new(ImageFactory));
+    // html2md puts `---` right below the text before it, making it a
+    // heading
+    handlers.insert("hr".to_owned(), Box::new(ThematicBreakFactory));
+    // markdown has no alignment or colors
+    handlers.insert("p".to_owned(), Box::new(ParagraphFactory));
+    handlers.insert(
+        "span".to_owned(),
+        Box::new(SpanFactory(options.keep_colors)),
+    );
+    for tag in &options.keep_tags {
+        if INLINE_ELEMENTS.contains(&tag.as_str()) {
+            handlers.insert(tag.clone(), Box::new(RawInlineFactory));
+        } else {
+            handlers.insert(tag.clone(), Box::new(RawBlockFactory));
+        }
+    }
+    parse_html_custom(html, &handlers).replace(BLANK_LINE, "")
+}
+
+/// Copies an element verbatim as html on lines of its own.
+struct RawBlockHandler;
+
+impl TagHandler for RawBlockHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        printer.insert_newline();
+        printer.insert_newline();
+        printer.append_str(&outer_html(tag));
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        printer.insert_newline();
+        printer.insert_newline();
+    }
+
+    fn skip_descendants(&self) -> bool {
+        true
+    }
+}
+
+struct RawBlockFactory;
+
+impl TagHandlerFactory for RawBlockFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(RawBlockHandler)
+    }
+}
+
+/// Writes `<hr>` as `---` in a block of its own.
+struct ThematicBreakHandler;
+
+impl TagHandler for ThematicBreakHandler {
+    fn handle(&mut self, _tag: &Handle, printer: &mut StructuredPrinter) {
+        printer.insert_newline();
+        printer.insert_newline();
+        printer.append_str("---");
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        printer.insert_newline();
+        printer.insert_newline();
+    }
+
+    fn skip_descendants(&self) -> bool {
+        true
+    }
+}
+
+struct ThematicBreakFactory;
+
+impl TagHandlerFactory for ThematicBreakFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(ThematicBreakHandler)
+    }
+}
+
+/// Writes `<audio>` and `<video>` as a shortcode named after the element
+/// with the urls of all its sources: `{{ video(sources=["a.mp4"]) }}`.
+struct MediaShortcodeHandler;
+
+impl TagHandler for MediaShortcodeHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        let name = match &tag.data {
+            NodeData::Element { name, .. } => name.local.to_string(),
+            _ => return,
+        };
+        let sources: Vec<String> = std::iter::once(tag.clone())
+            .chain(
+                tag.children
+                    .borrow()
+                    .iter()
+                    .filter(|child| tag_name(child) == Some("source"))
+                    .cloned(),
+            )
+            .filter_map(|node| attribute(&node, "src"))
+            .map(|src| format!("\"{}\"", src.replace('"', "%22")))
+            .collect();
+        let mut arguments = format!("sources=[{}]", sources.join(", "));
+        if let Some(poster) = attribute(tag, "poster") {
+            arguments.push_str(&format!(", poster=\"{}\"", poster.replace('"', "%22")));
+        }
+        printer.insert_newline();
+        printer.insert_newline();
+        printer.append_str(&format!("{{{{ {}({}) }}}}", name, arguments));
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        printer.insert_newline();
+        printer.insert_newline();
+    }
+
+    fn skip_descendants(&self) -> bool {
+        true
+    }
+}
+
+struct MediaShortcodeFactory;
+
+impl TagHandlerFactory for MediaShortcodeFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(MediaShortcodeHandler)
+    }
+}
+
+fn tag_name(node: &Handle) -> Option<&str> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(&name.local),
+        _ => None,
+    }
+}
+
+/// Value of attribute `name` of an element.
+fn attribute(node: &Handle, name: &str) -> Option<String> {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|attr| &*attr.name.local == name)
+            .map(|attr| attr.value.to_string()),
+        _ => None,
+    }
+}
+
+/// Elements which flow with the text around them.
+const INLINE_ELEMENTS: &[&str] = &[
+    "a", "abbr", "b", "cite", "code", "del", "dfn", "em", "i", "ins", "kbd", "mark", "q", "s",
+    "samp", "small", "span", "strong", "sub", "sup", "time", "u", "var",
+];
+
+/// Copies an element verbatim as html within its paragraph.
+struct RawInlineHandler;
+
+impl TagHandler for RawInlineHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        printer.append_str(&outer_html(tag));
+    }
+
+    fn after_handle(&mut self, _printer: &mut StructuredPrinter) {}
+
+    fn skip_descendants(&self) -> bool {
+        true
+    }
+}
+
+struct RawInlineFactory;
+
+impl TagHandlerFactory for RawInlineFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(RawInlineHandler)
+    }
+}
+
+/// Value of the css `property` in the `style` attribute of an element.
+fn style(tag: &Handle, property: &str) -> Option<String> {
+    let attrs = match &tag.data {
+        NodeData::Element { attrs, .. } => attrs.borrow(),
+        _ => return None,
+    };
+    let style = attrs.iter().find(|attr| &*attr.name.local == "style")?;
+    style.value.split(';').find_map(|declaration| {
+        let (name, value) = declaration.split_once(':')?;
+        Some(value.trim().to_lowercase()).filter(|_| name.trim().eq_ignore_ascii_case(property))
+    })
+}
+
+/// Separates paragraphs like html2md does, but keeps centered and right
+/// aligned ones as html, as markdown can't align text.
+#[derive(Default)]
+struct ParagraphHandler {
+    raw: bool,
+}
+
+impl TagHandler for ParagraphHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        printer.insert_newline();
+        printer.insert_newline();
+        let align = style(tag, "text-align").filter(|align| align == "center" || align == "right");
+        if let Some(align) = align {
+            self.raw = true;
+            printer.append_str(&format!("<p style=\"text-align: {}\">", align));
+            for child in tag.children.borrow().iter() {
+                printer.append_str(&outer_html(child));
+            }
+            printer.append_str("</p>");
+        }
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        printer.insert_newline();
+        printer.insert_newline();
+    }
+
+    fn skip_descendants(&self) -> bool {
+        self.raw
+    }
+}
+
+struct ParagraphFactory;
+
+impl TagHandlerFactory for ParagraphFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(ParagraphHandler::default())
+    }
+}
+
+/// Keeps colored `<span>`s as html when asked to, and otherwise drops
+/// the color with a warning.
+struct SpanHandler {
+    keep_colors: bool,
+    raw: bool,
+}
+
+impl TagHandler for SpanHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        let color = match style(tag, "color") {
+            Some(color) => color,
+            None => return,
+        };
+        if self.keep_colors {
+            self.raw = true;
+            printer.append_str(&format!("<span style=\"color: {}\">", color));
+            for child in tag.children.borrow().iter() {
+                printer.append_str(&outer_html(child));
+            }
+            printer.append_str("</span>");
+        } else {
+            warn!("Dropping text color {}", color);
+        }
+    }
+
+    fn after_handle(&mut self, _printer: &mut StructuredPrinter) {}
+
+    fn skip_descendants(&self) -> bool {
+        self.raw
+    }
+}
+
+struct SpanFactory(bool);
+
+impl TagHandlerFactory for SpanFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(SpanHandler {
+            keep_colors: self.0,
+            raw: false,
+        })
+    }
+}
+
+/// Writes the text of a `<zola-raw>` element as is.
+struct RawTextHandler;
+
+impl TagHandler for RawTextHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        for child in tag.children.borrow().iter() {
+            if let NodeData::Text { contents } = &child.data {
+                printer.append_str(&contents.borrow());
+            }
+        }
+    }
+
+    fn after_handle(&mut self, _printer: &mut StructuredPrinter) {}
+
+    fn skip_descendants(&self) -> bool {
+        true
+    }
+}
+
+struct RawTextFactory;
+
+impl TagHandlerFactory for RawTextFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(RawTextHandler)
+    }
+}
+
+/// Attributes markdown links can't express.
+const LINK_ATTRIBUTES: &[&str] = &["rel", "target", "name"];
+
+/// Writes links with attributes like `rel="nofollow"` as html
+/// `<a>` tags, and other links as markdown.
+#[derive(Default)]
+struct LinkHandler {
+    start: usize,
+    url: String,
+    raw: bool,
+}
+
+impl TagHandler for LinkHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        if let NodeData::Element { attrs, .. } = &tag.data {
+            let attrs = attrs.borrow();
+            self.raw = attrs
+                .iter()
+                .any(|attr| LINK_ATTRIBUTES.contains(&&*attr.name.local));
+            self.url = attrs
+                .iter()
+                .find(|attr| &*attr.name.local == "href")
+                .map(|attr| attr.value.to_string())
+                .unwrap_or_default();
+        }
+        if self.raw {
+            printer.append_str(&start_tag(tag));
+        }
+        self.start = printer.data.len();
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        if self.raw {
+            printer.append_str("</a>");
+        } else {
+            printer.insert_str(self.start, "[");
+            printer.append_str(&format!("]({})", self.url));
+        }
+    }
+}
+
+struct LinkFactory;
+
+impl TagHandlerFactory for LinkFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(LinkHandler::default())
+    }
+}
+
+/// Attributes markdown images can't express.
+const IMAGE_SIZE_ATTRIBUTES: &[&str] = &["width", "height", "align"];
+
+/// Writes images as `![alt](src "title")`, or as html when they are
+/// sized.
+struct ImageHandler;
+
+impl TagHandler for ImageHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        let sized = IMAGE_SIZE_ATTRIBUTES
+            .iter()
+            .any(|name| attribute(tag, name).is_some());
+        if sized {
+            printer.append_str(&start_tag(tag));
+            return;
+        }
+        let alt = attribute(tag, "alt").unwrap_or_default();
+        let alt = alt
+            .split_whitespace()
+            .collect::<Vec<_>>()
+            .join(" ")
+            .replace('[', "\\[")
+            .replace(']', "\\]");
+        let src = attribute(tag, "src")
+            .unwrap_or_default()
+            .replace(' ', "%20");
+        let title = match attribute(tag, "title").filter(|title| !title.trim().is_empty()) {
+            Some(title) => format!(" \"{}\"", title.trim().replace('"', "\\\"")),
+            None => String::new(),
+        };
+        printer.append_str(&format!("![{}]({}{})", alt, src, title));
+    }
+
+    fn after_handle(&mut self, _printer: &mut StructuredPrinter) {}
+}
+
+struct ImageFactory;
+
+impl TagHandlerFactory for ImageFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(ImageHandler)
+    }
+}
+
+/// Indentation of list item contents, so nesting looks the same for
+/// ordered and unordered lists.
+const LIST_INDENT: usize = 4;
+
+/// Lists the printer is currently inside of, innermost first.
+fn list_hierarchy(printer: &StructuredPrinter) -> Vec<&str> {
+    printer
+        .parent_chain
+        .iter()
+        .rev()
+        .map(String::as_str)
+        .filter(|tag| ["ul", "ol", "menu"].contains(tag))
+        .collect()
+}
+
+/// Starts a list on a new line, separated from surrounding paragraphs
+/// unless it is nested in another list.
+#[derive(Default)]
+struct ListHandler {
+    nested: bool,
+}
+
+impl TagHandler for ListHandler {
+    fn handle(&mut self, _tag: &Handle, printer: &mut StructuredPrinter) {
+        self.nested = !list_hierarchy(printer).is_empty();
+        printer.insert_newline();
+        if !self.nested {
+            printer.insert_newline();
+        }
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        printer.insert_newline();
+        if !self.nested {
+            printer.insert_newline();
+        }
+    }
+}
+
+struct ListFactory;
+
+impl TagHandlerFactory for ListFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(ListHandler::default())
+    }
+}
+
+/// Writes a list marker and indents the rest of the item under it.
+#[derive(Default)]
+struct ListItemHandler {
+    start: usize,
+    indent: usize,
+}
+
+impl TagHandler for ListItemHandler {
+    fn handle(&mut self, _tag: &Handle, printer: &mut StructuredPrinter) {
+        let marker = match list_hierarchy(printer).first() {
+            Some(&"ol") => {
+                let depth = printer.parent_chain.len();
+                let order = printer.siblings[&depth]
+                    .iter()
+                    .filter(|tag| *tag == "li")
+                    .count()
+                    + 1;
+                format!("{}. ", order)
+            }
+            Some(_) => "* ".to_owned(),
+            // html5ever wraps stray items in a list, so this shouldn't happen
+            None => String::new(),
+        };
+        if !printer.data.is_empty() && !printer.data.ends_with('\n') {
+            printer.insert_newline();
+        }
+        printer.append_str(&marker);
+        self.start = printer.data.len();
+        self.indent = LIST_INDENT.max(marker.len());
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        // `<p>` inside `<li>` shouldn't leave the marker on a line of its own
+        let content = printer.data[self.start..]
+            .trim_start_matches(['\n', ' '])
+            .trim_end()
+            .replace('\n', &format!("\n{}", " ".repeat(self.indent)));
+        printer.data.truncate(self.start);
+        printer.append_str(&content);
+    }
+}
+
+struct ListItemFactory;
+
+impl TagHandlerFactory for ListItemFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(ListItemHandler::default())
+    }
+}
+
+/// Quotes every line of the contents, with single empty quoted lines
+/// between paragraphs.
+#[derive(Default)]
+struct BlockquoteHandler {
+    start: usize,
+}
+
+impl TagHandler for BlockquoteHandler {
+    fn handle(&mut self, _tag: &Handle, printer: &mut StructuredPrinter) {
+        printer.insert_newline();
+        printer.insert_newline();
+        self.start = printer.data.len();
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        let content = printer.data[self.start..].trim().to_owned();
+        printer.data.truncate(self.start);
+        let mut quoted = Vec::new();
+        for line in content.lines().map(str::trim_end) {
+            if line.is_empty() {
+                if quoted.last() != Some(&">".to_owned()) {
+                    quoted.push(">".to_owned());
+                }
+            } else {
+                quoted.push(format!("> {}", line));
+            }
+        }
+        printer.append_str(&quoted.join("\n"));
+        printer.insert_newline();
+        printer.insert_newline();
+    }
+}
+
+struct BlockquoteFactory;
+
+impl TagHandlerFactory for BlockquoteFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(BlockquoteHandler::default())
+    }
+}
+
+/// Writes `<dt>` terms in bold and `<dd>` definitions after them in
+/// the given style.
+struct DefinitionHandler {
+    style: DefinitionList,
+    start: usize,
+    term: bool,
+}
+
+impl TagHandler for DefinitionHandler {
+    fn handle(&mut self, tag: &Handle, printer: &mut StructuredPrinter) {
+        if let NodeData::Element { name, .. } = &tag.data {
+            self.term = &*name.local == "dt";
+        }
+        if self.term {
+            if !printer.data.is_empty() {
+                printer.insert_newline();
+                printer.insert_newline();
+            }
+        } else {
+            match self.style {
+                DefinitionList::Colon => {
+                    printer.insert_newline();
+                    printer.append_str(": ");
+                }
+                DefinitionList::Dash => printer.append_str(" — "),
+            }
+        }
+        self.start = printer.data.len();
+    }
+
+    fn after_handle(&mut self, printer: &mut StructuredPrinter) {
+        let content = printer.data[self.start..].trim().to_owned();
+        printer.data.truncate(self.start);
+        if self.term {
+            printer.append_str(&format!("**{}**", content));
+        } else {
+            printer.append_str(&content.replace('\n', "\n  "));
+        }
+    }
+}
+
+struct DefinitionFactory(DefinitionList);
+
+impl TagHandlerFactory for DefinitionFactory {
+    fn instantiate(&self) -> Box<dyn TagHandler> {
+        Box::new(DefinitionHandler {
+            style: self.0,
+            start: 0,
+            term: false,
+        })
+    }
+}
+
+/// Elements which have no closing tag.
+const VOID_ELEMENTS: &[&str] = &[
+    "area", "base", "br", "col", "embed", "hr", "img", "input", "link", "meta", "source", "track",
+    "wbr",
+];
+
+/// Serialize a node with all its descendants back to html, with the
+/// text of `<zola-raw>` elements as is.
+fn outer_html(node: &Handle) -> String {
+    match &node.data {
+        NodeData::Text { contents } => escape(&contents.borrow(), false),
+        NodeData::Element { name, .. } if &*name.local == "zola-raw" => node
+            .children
+            .borrow()
+            .iter()
+            .filter_map(|child| match &child.data {
+                NodeData::Text { contents } => Some(contents.borrow().to_string()),
+                _ => None,
+            })
+            .collect(),
+        NodeData::Element { name, .. } => {
+            let mut html = start_tag(node);
+            if VOID_ELEMENTS.contains(&&*name.local) {
+                return html;
+            }
+            for child in node.children.borrow().iter() {
+                html.push_str(&outer_html(child));
+            }
+            html.push_str(&format!("</{}>", name.local));
+            html
+        }
+        _ => String::new(),
+    }
+}
+
+/// Opening tag of an element with all its attributes.
+fn start_tag(node: &Handle) -> String {
+    let mut html = String::new();
+    if let NodeData::Element { name, attrs, .. } = &node.data {
+        html.push_str(&format!("<{}", name.local));
+        for attr in attrs.borrow().iter() {
+            html.push_str(&format!(
+                " {}=\"{}\"",
+                attr.name.local,
+                escape(&attr.value, true)
+            ));
+        }
+        html.push('>');
+    }
+    html
+}
+
+fn escape(text: &str, in_attribute: bool) -> String {
+    let text = text.replace('&', "&amp;");
+    if in_attribute {
+        text.replace('"', "&quot;")
+    } else {
+        text.replace('<', "&lt;").replace('>', "&gt;")
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::html_to_markdown;
+    use crate::{DefinitionList, Options};
+
+    #[test]
+    fn nested_lists_are_indented_consistently() {
+        assert_eq!(
+            html_to_markdown(
+                "<ul><li>a<ol><li>b</li><li>c</li></ol></li><li>d</li></ul>\
+                 <ol><li>one<ul><li>x</li></ul></li><li>two</li></ol>",
+                &Options::default()
+            ),
+            "* a\n    1. b\n    2. c\n* d\n\n1. one\n    * x\n2. two"
+        );
+    }
+
+    #[test]
+    fn iframes_are_kept_on_their_own_lines() {
+        assert_eq!(
+            html_to_markdown(
+                r#"<p>Map: <iframe src="https://maps.example.com/?a=1&amp;b=2" width="600" allowfullscreen=""></iframe></p><p>Pen:</p><iframe src="https://codepen.io/x/embed/y"></iframe>"#,
+                &Options::default(),
+            ),
+            "Map:\n\n\
+             <iframe src=\"https://maps.example.com/?a=1&amp;b=2\" width=\"600\" allowfullscreen=\"\"></iframe>\n\n\
+             Pen:\n\n\
+             <iframe src=\"https://codepen.io/x/embed/y\"></iframe>"
+        );
+    }
+
+    #[test]
+    fn links_with_attributes_stay_html() {
+        let options = Options {
+            keep_link_attributes: true,
+            ..Options::default()
+        };
+        assert_eq!(
+            html_to_markdown(
+                r#"<p>Buy <a href="https://shop.example.com" rel="nofollow sponsored">this <b>thing</b></a> or <a href="https://example.com">that</a></p>"#,
+                &options
+            ),
+            "Buy <a href=\"https://shop.example.com\" rel=\"nofollow sponsored\">this **thing**</a> or [that](https://example.com)"
+        );
+    }
+
+    #[test]
+    fn definition_lists_become_bold_terms() {
+        // Given a glossary with two terms
+        let html = "<p>Glossary:</p><dl><dt>Zola</dt><dd>A <em>static</em> site generator</dd>\
+                    <dt>Tera</dt><dd>Its template engine</dd></dl><p>End</p>";
+
+        // When we convert it in either style
+        let dash = Options {
+            definition_lists: DefinitionList::Dash,
+            ..Options::default()
+        };
+
+        // Then every term is followed by its definition
+        assert_eq!(
+            html_to_markdown(html, &Options::default()),
+            "Glossary:\n\n**Zola**\n: A *static* site generator\n\n**Tera**\n: Its template engine\n\nEnd"
+        );
+        assert_eq!(
+            html_to_markdown(html, &dash),
+            "Glossary:\n\n**Zola** — A *static* site generator\n\n**Tera** — Its template engine\n\nEnd"
+        );
+    }
+
+    #[test]
+    fn kept_tags_stay_html() {
+        let options = Options {
+            keep_tags: vec!["details".to_owned(), "kbd".to_owned()],
+            ..Options::default()
+        };
+        assert_eq!(
+            html_to_markdown(
+                "<details><summary>More</summary><p>Hidden <b>text</b></p></details>\
+                 <p>Press <kbd>Ctrl</kbd> and <em>go</em></p>",
+                &options
+            ),
+            "<details><summary>More</summary><p>Hidden <b>text</b></p></details>\n\n\
+             Press <kbd>Ctrl</kbd> and *go*"
+        );
+    }
+
+    #[test]
+    fn alignment_is_kept_and_colors_follow_the_option() {
+        let html = r#"<p style="text-align:center;">Centered <b>text</b></p><p>Some <span style="font-weight: bold; color: #FF0000">red</span> words</p>"#;
+        assert_eq!(
+            html_to_markdown(html, &Options::default()),
+            "<p style=\"text-align: center\">Centered <b>text</b></p>\n\nSome red words"
+        );
+
+        let options = Options {
+            keep_colors: true,
+            ..Options::default()
+        };
+        assert_eq!(
+            html_to_markdown(html, &options),
+            "<p style=\"text-align: center\">Centered <b>text</b></p>\n\n\
+             Some <span style=\"color: #ff0000\">red</span> words"
+        );
+    }
+
+    #[test]
+    fn videos_keep_all_their_sources() {
+        let html = r#"<p>Watch:</p><video controls poster="https://example.com/p.jpg"><source src="https://example.com/v.mp4" type="video/mp4"><source src="https://example.com/v.webm" type="video/webm"></video>"#;
+        assert_eq!(
+            html_to_markdown(html, &Options::default()),
+            "Watch:\n\n<video controls=\"\" poster=\"https://example.com/p.jpg\">\
+             <source src=\"https://example.com/v.mp4\" type=\"video/mp4\">\
+             <source src=\"https://example.com/v.webm\" type=\"video/webm\"></video>"
+        );
+
+        let options = Options {
+            media_shortcodes: true,
+            ..Options::default()
+        };
+        assert_eq!(
+            html_to_markdown(html, &options),
+            "Watch:\n\n{{ video(sources=[\"https://example.com/v.mp4\", \"https://example.com/v.webm\"], \
+             poster=\"https://example.com/p.jpg\") }}"
+        );
+    }
+
+    #[test]
+    fn images_keep_their_alt_text_and_title() {
+        assert_eq!(
+            html_to_markdown(
+                r#"<p><img src="/a cat.png" alt="A [sleeping] cat" title="Tom, &quot;the&quot; cat"> <img src="/b.png" alt=""></p>"#,
+                &Options::default(),
+            ),
+            r#"![A \[sleeping\] cat](/a%20cat.png "Tom, \"the\" cat") ![](/b.png)"#
+        );
+    }
+}
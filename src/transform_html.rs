@@ -1,18 +1,43 @@
-use html2md::NodeData;
+use crate::tag_handlers::BLANK_LINE;
+use crate::{Options, Shortcodes};
 use html5ever::QualName;
 use html5ever::{tendril::TendrilSink, tree_builder::TreeBuilderOpts, ParseOpts};
+use log::*;
 use markup5ever_rcdom::Node;
+use markup5ever_rcdom::NodeData;
 use markup5ever_rcdom::RcDom;
 use markup5ever_rcdom::SerializableHandle;
 use regex::Regex;
-use std::borrow::Borrow;
+use std::borrow::{Borrow, Cow};
 use std::cell::RefCell;
+use std::collections::HashMap;
 use std::ops::Deref;
 use std::rc::Rc;
+use std::sync::LazyLock;
+
+/// Gaps between paragraphs.
+static NEWLINES: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"\n\n+").unwrap());
+/// Some plugins export posts as whole `<html><body>` documents.
+static DOCUMENT: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"(?i)<(?:!doctype|html|head|body)[\s>]").unwrap());
 
 /// Wordpress does some transformations on its HTML before it displays it.
 /// Attempt to recreate them here.
-pub fn transform_html(content: &str) -> String {
+pub fn transform_html(content: &str, options: &Options) -> String {
+    // Some exports use Windows line endings, which hide paragraph gaps.
+    let normalized;
+    let content = if content.contains('\r') {
+        normalized = content.replace("\r\n", "\n").replace('\r', "\n");
+        normalized.as_str()
+    } else {
+        content
+    };
+    let code = expand_code_shortcodes(content);
+    let embeds = expand_embed_blocks(&code, &options.shortcodes);
+    let expanded = expand_media_shortcodes(&embeds);
+    let math = expand_latex_shortcodes(&expanded);
+    let content: &str = &math;
+
     let opts = ParseOpts {
         tree_builder: TreeBuilderOpts {
             drop_doctype: true,
@@ -25,18 +50,14 @@ pub fn transform_html(content: &str) -> String {
     let html = find_child_element(dom.document.clone(), "html");
     let body = find_child_element(html, "body");
 
-    let newlines = Regex::new(r"\n\n+").unwrap();
-
-    let mut i = 0;
     let mut texts: Vec<(isize, String)> = Vec::new();
-    for child in body.children.borrow().iter() {
+    for (i, child) in body.children.borrow().iter().enumerate() {
         if let NodeData::Text { contents } = child.data.borrow() {
             let text = contents.borrow().deref().deref().to_owned();
-            if newlines.is_match(&text) {
-                texts.push((i, text));
+            if NEWLINES.is_match(&text) {
+                texts.push((i as isize, text));
             }
         }
-        i += 1;
     }
 
     let mut changed = false;
@@ -47,7 +68,7 @@ pub fn transform_html(content: &str) -> String {
         body.children.borrow_mut().remove((i + offset) as usize);
         offset -= 1;
 
-        for chunk in itertools::intersperse(newlines.split(&text), &"\n\n") {
+        for chunk in itertools::intersperse(NEWLINES.split(&text), "\n\n") {
             if chunk == "\n\n" {
                 body.children
                     .borrow_mut()
@@ -62,7 +83,17 @@ pub fn transform_html(content: &str) -> String {
         }
     }
 
-    if changed {
+    changed |= escape_zola_syntax(&body);
+    changed |= convert_quotes(&body, &options.shortcodes);
+    changed |= convert_mathml(&body);
+    changed |= unwrap_layout_tables(&body);
+    changed |= convert_footnotes(&body);
+    changed |= convert_emoji(&body);
+    changed |= convert_separators(&body);
+    changed |= convert_anchors(&body);
+
+    // only the body of a whole document is the post
+    if changed || DOCUMENT.is_match(content) {
         let mut ret = Vec::new();
         let ser: SerializableHandle = body.clone().into();
         html5ever::serialize(&mut ret, &ser, Default::default())
@@ -73,6 +104,232 @@ pub fn transform_html(content: &str) -> String {
     }
 }
 
+/// Source attributes of `[audio]` and `[video]` shortcodes with the
+/// mime type of their files.
+const MEDIA_SOURCES: &[(&str, &str)] = &[
+    ("src", ""),
+    ("mp3", "audio/mpeg"),
+    ("ogg", "audio/ogg"),
+    ("oga", "audio/ogg"),
+    ("wav", "audio/wav"),
+    ("m4a", "audio/mp4"),
+    ("wma", "audio/x-ms-wma"),
+    ("mp4", "video/mp4"),
+    ("m4v", "video/mp4"),
+    ("webm", "video/webm"),
+    ("ogv", "video/ogg"),
+    ("wmv", "video/x-ms-wmv"),
+    ("flv", "video/x-flv"),
+];
+
+/// Self-hosted media is embedded with `[audio mp3="..."]` and
+/// `[video mp4="..."]` shortcodes, which WordPress renders as
+/// `<audio>` and `<video>` tags with a `<source>` per file.
+fn expand_media_shortcodes(content: &str) -> Cow<'_, str> {
+    static SHORTCODE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r#"\[(audio|video)((?:\s+[\w-]+=(?:"[^"]*"|'[^']*'))*)\s*/?\](?:\s*\[/(?:audio|video)\])?"#,
+        )
+        .unwrap()
+    });
+    static ATTRIBUTE: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"([\w-]+)=(?:"([^"]*)"|'([^']*)')"#).unwrap());
+
+    SHORTCODE.replace_all(content, |captures: &regex::Captures| {
+        let tag = &captures[1];
+        let attributes: Vec<(String, String)> = ATTRIBUTE
+            .captures_iter(&captures[2])
+            .map(|attr| {
+                let value = attr.get(2).or_else(|| attr.get(3)).unwrap().as_str();
+                (attr[1].to_lowercase(), value.to_owned())
+            })
+            .collect();
+
+        let mut html = format!("<{} controls", tag);
+        for (name, value) in &attributes {
+            if ["width", "height", "poster"].contains(&name.as_str()) {
+                html.push_str(&format!(" {}=\"{}\"", name, value));
+            }
+        }
+        html.push('>');
+        for (name, value) in &attributes {
+            if let Some((_, mime)) = MEDIA_SOURCES.iter().find(|(source, _)| source == name) {
+                html.push_str(&format!("<source src=\"{}\"", value));
+                if !mime.is_empty() {
+                    html.push_str(&format!(" type=\"{}\"", mime));
+                }
+                html.push('>');
+            }
+        }
+        html.push_str(&format!("</{}>", tag));
+        html
+    })
+}
+
+/// Block editor embeds keep the provider and url as json in their
+/// opening comment:
+///
+/// ```html
+/// <!-- wp:embed {"url":"https://youtu.be/id","providerNameSlug":"youtube"} -->
+/// <figure class="wp-block-embed">...</figure>
+/// <!-- /wp:embed -->
+/// ```
+///
+/// Known providers become zola shortcodes, others a plain link.
+fn expand_embed_blocks<'a>(content: &'a str, shortcodes: &Shortcodes) -> Cow<'a, str> {
+    static BLOCK: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?s)<!-- wp:embed (\{.*?\}) /?-->(?:.*?<!-- /wp:embed -->)?").unwrap()
+    });
+
+    BLOCK.replace_all(content, |captures: &regex::Captures| {
+        let json = &captures[1];
+        let url = match json_string(json, "url") {
+            Some(url) => url,
+            None => return captures[0].to_owned(),
+        };
+        let provider = json_string(json, "providerNameSlug").unwrap_or_default();
+        match embed_shortcode(&provider, &url, shortcodes) {
+            Some(shortcode) => format!("<p><zola-raw>{}</zola-raw></p>", shortcode),
+            None => {
+                let href = url.replace('&', "&amp;").replace('"', "&quot;");
+                let text = url.replace('&', "&amp;").replace('<', "&lt;");
+                format!("<p><a href=\"{}\">{}</a></p>", href, text)
+            }
+        }
+    })
+}
+
+/// SyntaxHighlighter's `[sourcecode language="python"]...[/sourcecode]`
+/// and `[code lang="js"]...[/code]` become fenced code blocks with the
+/// language, if any.
+fn expand_code_shortcodes(content: &str) -> Cow<'_, str> {
+    static CODE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(
+            r"(?s)\[sourcecode((?:\s+[^\]]*)?)\](.*?)\[/sourcecode\]|\[code((?:\s+[^\]]*)?)\](.*?)\[/code\]",
+        )
+        .unwrap()
+    });
+    static LANGUAGE: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r#"\b(?:language|lang)\s*=\s*(?:"([\w+#.-]*)"|'([\w+#.-]*)'|([\w+#.-]+))"#)
+            .unwrap()
+    });
+    static BACKTICKS: LazyLock<Regex> = LazyLock::new(|| Regex::new(r"`{3,}").unwrap());
+
+    CODE.replace_all(content, |captures: &regex::Captures| {
+        let (attributes, code) = match captures.get(2) {
+            Some(code) => (&captures[1], code.as_str()),
+            None => (&captures[3], &captures[4]),
+        };
+        let language = LANGUAGE
+            .captures(attributes)
+            .and_then(|language| (1..=3).find_map(|i| language.get(i)))
+            .map_or("", |language| language.as_str());
+        let language = match language {
+            "plain" | "text" => "",
+            "jscript" | "javascript" => "js",
+            "shell" => "bash",
+            language => language,
+        };
+        // a fence longer than any run of backticks in the code
+        let longest = BACKTICKS
+            .find_iter(code)
+            .map(|run| run.len())
+            .max()
+            .unwrap_or(2);
+        let fence = "`".repeat(longest + 1);
+        // the source stays html, so a bare `<` must not start a tag,
+        // while entities are decoded by parsing it
+        let code = code.trim_matches('\n').replace('<', "&lt;");
+        let code = ZOLA_TAG.replace_all(&code, escape_zola_tag);
+        let code: Vec<String> = code
+            .split('\n')
+            .map(|line| match line.trim().is_empty() {
+                true => format!("{}{}", line, BLANK_LINE),
+                false => line.to_owned(),
+            })
+            .collect();
+        let code = code.join("\n");
+        format!(
+            "<p><zola-raw>{}{}\n{}\n{}</zola-raw></p>",
+            fence, language, code, fence
+        )
+    })
+}
+
+/// LaTeX plugins render `[latex]x^2[/latex]` and Jetpack `$latex x^2$`.
+/// Both become `$x^2$` for KaTeX, or `$$x^2$$` on a line of its own for
+/// `[latex display="true"]`, kept verbatim in `<zola-raw>`.
+fn expand_latex_shortcodes(content: &str) -> Cow<'_, str> {
+    static LATEX: LazyLock<Regex> = LazyLock::new(|| {
+        Regex::new(r"(?s)\[latex([^\]]*)\](.*?)\[/latex\]|\$latex\s(.*?)\$").unwrap()
+    });
+    static DISPLAY: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#"display\s*=\s*["']?(?:true|1|yes|block)"#).unwrap());
+
+    LATEX.replace_all(content, |captures: &regex::Captures| {
+        let (tex, display) = match captures.get(2) {
+            Some(tex) => (tex.as_str(), DISPLAY.is_match(&captures[1])),
+            None => (&captures[3], false),
+        };
+        // the source stays html, so a bare `<` must not start a tag
+        let tex = tex.trim().replace('<', "&lt;");
+        let tex = ZOLA_TAG.replace_all(&tex, escape_zola_tag);
+        if display {
+            format!("<p><zola-raw>$${}$$</zola-raw></p>", tex)
+        } else {
+            format!("<zola-raw>${}$</zola-raw>", tex)
+        }
+    })
+}
+
+/// Zola's built-in shortcode, or the theme's one named in `shortcodes`,
+/// for a video `url` of a known provider.
+fn embed_shortcode(provider: &str, url: &str, shortcodes: &Shortcodes) -> Option<String> {
+    static YOUTUBE_ID: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"(?:[?&]v=|youtu\.be/|/embed/|/shorts/)([\w-]+)").unwrap());
+    static VIMEO_ID: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"vimeo\.com/(?:video/)?(\d+)").unwrap());
+
+    let (name, id) = match provider {
+        "youtube" => (&shortcodes.youtube, YOUTUBE_ID.captures(url)?[1].to_owned()),
+        "vimeo" => (&shortcodes.vimeo, VIMEO_ID.captures(url)?[1].to_owned()),
+        _ => return None,
+    };
+    Some(format!("{{{{ {}(id=\"{}\") }}}}", name, id))
+}
+
+/// Value of a string field of a flat json object.
+fn json_string(json: &str, key: &str) -> Option<String> {
+    static FIELD: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r#""(\w+)"\s*:\s*"((?:[^"\\]|\\.)*)""#).unwrap());
+
+    let raw = FIELD
+        .captures_iter(json)
+        .find(|field| &field[1] == key)?
+        .get(2)?
+        .as_str()
+        .to_owned();
+
+    let mut value = String::new();
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            value.push(c);
+            continue;
+        }
+        match chars.next()? {
+            'n' => value.push('\n'),
+            't' => value.push('\t'),
+            'u' => {
+                let code: String = chars.by_ref().take(4).collect();
+                value.push(char::from_u32(u32::from_str_radix(&code, 16).ok()?)?);
+            }
+            escaped => value.push(escaped),
+        }
+    }
+    Some(value)
+}
+
 fn text_node(text: &str) -> Rc<Node> {
     Node::new(NodeData::Text {
         contents: RefCell::new(text.into()),
@@ -80,14 +337,540 @@ fn text_node(text: &str) -> Rc<Node> {
 }
 
 fn p_node() -> Rc<Node> {
+    element_node("p")
+}
+
+fn element_node(tag: &str) -> Rc<Node> {
     Node::new(NodeData::Element {
-        name: QualName::new(None, "".into(), "p".into()),
+        name: QualName::new(None, "".into(), tag.into()),
         attrs: RefCell::new(Vec::new()),
         template_contents: RefCell::new(None),
         mathml_annotation_xml_integration_point: false,
     })
 }
 
+/// Tag name of an element node.
+fn tag_name(node: &Node) -> Option<&str> {
+    match &node.data {
+        NodeData::Element { name, .. } => Some(&name.local),
+        _ => None,
+    }
+}
+
+fn is_element(node: &Node, tag: &str) -> bool {
+    tag_name(node).is_some_and(|name| name.eq_ignore_ascii_case(tag))
+}
+
+/// Value of attribute `name` of an element node.
+fn attr(node: &Node, name: &str) -> Option<String> {
+    match &node.data {
+        NodeData::Element { attrs, .. } => attrs
+            .borrow()
+            .iter()
+            .find(|attr| attr.name.local.eq_str_ignore_ascii_case(name))
+            .map(|attr| attr.value.to_string()),
+        _ => None,
+    }
+}
+
+/// Whether the `class` attribute of `node` lists `class`.
+fn has_class(node: &Node, class: &str) -> bool {
+    attr(node, "class").is_some_and(|classes| classes.split_whitespace().any(|c| c == class))
+}
+
+fn set_attr(node: &Node, name: &str, value: &str) {
+    if let NodeData::Element { attrs, .. } = &node.data {
+        for attr in attrs.borrow_mut().iter_mut() {
+            if attr.name.local.eq_str_ignore_ascii_case(name) {
+                attr.value = value.into();
+            }
+        }
+    }
+}
+
+/// Text of `node` and everything below it.
+fn text_content(node: &Node) -> String {
+    let mut text = String::new();
+    for node in descendants(node) {
+        if let NodeData::Text { contents } = &node.data {
+            text.push_str(&contents.borrow());
+        }
+    }
+    text
+}
+
+/// Element children of `node`, skipping text and comments.
+fn child_elements(node: &Node) -> Vec<Rc<Node>> {
+    node.children
+        .borrow()
+        .iter()
+        .filter(|child| tag_name(child).is_some())
+        .cloned()
+        .collect()
+}
+
+/// All nodes below `node` in document order.
+fn descendants(node: &Node) -> Vec<Rc<Node>> {
+    let mut nodes = Vec::new();
+    for child in node.children.borrow().iter() {
+        nodes.push(child.clone());
+        nodes.extend(descendants(child));
+    }
+    nodes
+}
+
+/// Walk the tree below `node` and replace every node for which
+/// `replace` returns new nodes. Replaced nodes are not descended into.
+fn replace_nodes(
+    node: &Node,
+    replace: &mut impl FnMut(&Rc<Node>) -> Option<Vec<Rc<Node>>>,
+) -> bool {
+    let mut changed = false;
+    let children = node.children.borrow().clone();
+    let mut new_children = Vec::with_capacity(children.len());
+    for child in children {
+        match replace(&child) {
+            Some(nodes) => {
+                changed = true;
+                new_children.extend(nodes);
+            }
+            None => {
+                changed |= replace_nodes(&child, replace);
+                new_children.push(child);
+            }
+        }
+    }
+    if changed {
+        *node.children.borrow_mut() = new_children;
+    }
+    changed
+}
+
+/// `{{ ... }}` and `{% ... %}`, which zola takes for shortcodes even in
+/// code blocks.
+static ZOLA_TAG: LazyLock<Regex> =
+    LazyLock::new(|| Regex::new(r"\{\{(.*?)\}\}|\{%(.*?)%\}").unwrap());
+
+/// Zola's escaped form of a `ZOLA_TAG` match, `{{/* ... */}}`, unless
+/// it is escaped already.
+fn escape_zola_tag(captures: &regex::Captures) -> String {
+    let (open, inner, close) = match captures.get(1) {
+        Some(inner) => ("{{", inner.as_str(), "}}"),
+        None => ("{%", &captures[2], "%}"),
+    };
+    if inner.starts_with("/*") && inner.ends_with("*/") {
+        captures[0].to_owned()
+    } else {
+        format!("{}/*{}*/{}", open, inner, close)
+    }
+}
+
+/// Zola treats `{{ ... }}` and `{% ... %}` in content as shortcodes
+/// and fails the build on unknown ones. Literal ones are turned into
+/// zola's escaped form, `{{/* ... */}}`, kept from markdown escaping in
+/// `<zola-raw>` like the shortcodes we generate ourselves.
+fn escape_zola_syntax(body: &Node) -> bool {
+    replace_nodes(body, &mut |node| {
+        if is_element(node, "zola-raw") {
+            return Some(vec![node.clone()]);
+        }
+        let text = match &node.data {
+            NodeData::Text { contents } => contents.borrow().to_string(),
+            _ => return None,
+        };
+        if !ZOLA_TAG.is_match(&text) {
+            return None;
+        }
+
+        let mut nodes = Vec::new();
+        let mut last = 0;
+        for captures in ZOLA_TAG.captures_iter(&text) {
+            let whole = captures.get(0).unwrap();
+            nodes.push(text_node(&text[last..whole.start()]));
+            let raw = element_node("zola-raw");
+            raw.children
+                .borrow_mut()
+                .push(text_node(&escape_zola_tag(&captures)));
+            nodes.push(raw);
+            last = whole.end();
+        }
+        nodes.push(text_node(&text[last..]));
+        Some(nodes)
+    })
+}
+
+/// Gutenberg renders `core/quote` as `<blockquote class="wp-block-quote">`
+/// and `core/pullquote` as `<figure class="wp-block-pullquote">` around a
+/// blockquote, both with an optional `<cite>`. Quotes become markdown
+/// blockquotes ending with the citation, pullquotes a `pullquote`
+/// shortcode for the theme to style.
+fn convert_quotes(body: &Node, shortcodes: &Shortcodes) -> bool {
+    replace_nodes(body, &mut |node| {
+        let pullquote = has_class(node, "wp-block-pullquote");
+        let regular = is_element(node, "blockquote") && has_class(node, "wp-block-quote");
+        if !pullquote && !regular {
+            return None;
+        }
+        let quote = if is_element(node, "blockquote") {
+            node.clone()
+        } else {
+            child_elements(node)
+                .into_iter()
+                .find(|child| is_element(child, "blockquote"))?
+        };
+        let cite = child_elements(&quote)
+            .into_iter()
+            .find(|child| is_element(child, "cite"))
+            .map(|cite| {
+                quote
+                    .children
+                    .borrow_mut()
+                    .retain(|child| !Rc::ptr_eq(child, &cite));
+                text_content(&cite).trim().to_owned()
+            })
+            .filter(|cite| !cite.is_empty());
+
+        // dropping a node empties the nodes below it, so they are moved out
+        let children = std::mem::take(&mut *quote.children.borrow_mut());
+
+        if pullquote {
+            let arguments = cite
+                .map(|cite| format!("cite=\"{}\"", cite.replace('"', "'")))
+                .unwrap_or_default();
+            let mut nodes = vec![raw_paragraph(&format!(
+                "{{% {}({}) %}}",
+                shortcodes.pullquote, arguments
+            ))];
+            nodes.extend(children);
+            nodes.push(raw_paragraph("{% end %}"));
+            Some(nodes)
+        } else {
+            let blockquote = element_node("blockquote");
+            blockquote.children.borrow_mut().extend(children);
+            if let Some(cite) = cite {
+                let p = p_node();
+                p.children
+                    .borrow_mut()
+                    .push(text_node(&format!("— {}", cite)));
+                blockquote.children.borrow_mut().push(p);
+            }
+            Some(vec![blockquote])
+        }
+    })
+}
+
+/// Old posts use tables only to lay out their content, which makes no
+/// sense as a markdown table. Tables without headers which are marked
+/// presentational or have a single column are replaced by the contents
+/// of their cells, each in a paragraph unless it has blocks of its own.
+fn unwrap_layout_tables(body: &Node) -> bool {
+    replace_nodes(body, &mut |node| {
+        if !is_element(node, "table") {
+            return None;
+        }
+        let rows = table_rows(node);
+        let cells: Vec<Vec<Rc<Node>>> = rows.iter().map(|row| child_elements(row)).collect();
+        let has_headers = cells.iter().flatten().any(|cell| is_element(cell, "th"))
+            || child_elements(node)
+                .iter()
+                .any(|child| is_element(child, "thead") || is_element(child, "caption"));
+        let presentational = attr(node, "role").is_some_and(|role| role == "presentation");
+        let single_column = cells.iter().all(|row| row.len() <= 1);
+        if has_headers || !(presentational || single_column) {
+            return None;
+        }
+
+        let mut nodes = Vec::new();
+        for cell in cells.into_iter().flatten() {
+            // dropping a node empties the nodes below it, so they are moved out
+            let children = std::mem::take(&mut *cell.children.borrow_mut());
+            let container = if children.iter().any(|child| is_block(child)) {
+                element_node("div")
+            } else {
+                p_node()
+            };
+            container.children.borrow_mut().extend(children);
+            unwrap_layout_tables(&container);
+            nodes.push(container);
+        }
+        Some(nodes)
+    })
+}
+
+/// `<tr>` elements of a table, directly or in its row groups.
+fn table_rows(table: &Node) -> Vec<Rc<Node>> {
+    let mut rows = Vec::new();
+    for child in child_elements(table) {
+        if is_element(&child, "tr") {
+            rows.push(child);
+        } else if ["thead", "tbody", "tfoot"]
+            .iter()
+            .any(|group| is_element(&child, group))
+        {
+            rows.extend(
+                child_elements(&child)
+                    .into_iter()
+                    .filter(|row| is_element(row, "tr")),
+            );
+        }
+    }
+    rows
+}
+
+/// Whether `node` is an element markdown puts on lines of its own.
+fn is_block(node: &Node) -> bool {
+    const BLOCKS: &[&str] = &[
+        "p",
+        "div",
+        "table",
+        "ul",
+        "ol",
+        "dl",
+        "blockquote",
+        "pre",
+        "figure",
+        "hr",
+        "h1",
+        "h2",
+        "h3",
+        "h4",
+        "h5",
+        "h6",
+    ];
+    BLOCKS.iter().any(|tag| is_element(node, tag))
+}
+
+/// MathML with its LaTeX source in an `x-tex` annotation becomes `$tex$`,
+/// or `$$tex$$` for `<math display="block">`.
+fn convert_mathml(body: &Node) -> bool {
+    replace_nodes(body, &mut |node| {
+        if !is_element(node, "math") {
+            return None;
+        }
+        let annotation = descendants(node).into_iter().find(|child| {
+            is_element(child, "annotation")
+                && attr(child, "encoding").as_deref() == Some("application/x-tex")
+        })?;
+        let tex = text_content(&annotation);
+        if attr(node, "display").as_deref() == Some("block") {
+            Some(vec![raw_paragraph(&format!("$${}$$", tex.trim()))])
+        } else {
+            let raw = element_node("zola-raw");
+            raw.children
+                .borrow_mut()
+                .push(text_node(&format!("${}$", tex.trim())));
+            Some(vec![raw])
+        }
+    })
+}
+
+/// `<p>` with `text` kept from markdown escaping.
+fn raw_paragraph(text: &str) -> Rc<Node> {
+    let raw = element_node("zola-raw");
+    raw.children.borrow_mut().push(text_node(text));
+    let p = p_node();
+    p.children.borrow_mut().push(raw);
+    p
+}
+
+/// Footnote plugins render references as `<sup><a href="#fn1">1</a></sup>`
+/// and definitions as a list of `<li id="fn1">`. Turn them into markdown
+/// footnotes: `[^1]` and `[^1]: definition`.
+fn convert_footnotes(body: &Node) -> bool {
+    let nodes = descendants(body);
+    let ids: Vec<String> = nodes
+        .iter()
+        .filter(|node| is_element(node, "li"))
+        .filter_map(|li| attr(li, "id"))
+        .collect();
+    let reference = |node: &Rc<Node>| -> Option<String> {
+        if !is_element(node, "sup") {
+            return None;
+        }
+        let link = match child_elements(node).as_slice() {
+            [link] => link.clone(),
+            _ => return None,
+        };
+        let id = attr(&link, "href")?.strip_prefix('#')?.to_owned();
+        if is_element(&link, "a") && ids.contains(&id) {
+            Some(id)
+        } else {
+            None
+        }
+    };
+    let referenced: Vec<String> = nodes.iter().filter_map(reference).collect();
+    if referenced.is_empty() {
+        return false;
+    }
+
+    replace_nodes(body, &mut |node| {
+        if let Some(id) = reference(node) {
+            return Some(vec![text_node(&format!("[^{}]", footnote_label(&id)))]);
+        }
+        let items = child_elements(node);
+        let is_definitions = (is_element(node, "ol") || is_element(node, "ul"))
+            && !items.is_empty()
+            && items
+                .iter()
+                .all(|item| attr(item, "id").is_some_and(|id| referenced.contains(&id)));
+        if !is_definitions {
+            return None;
+        }
+        let definitions = items.iter().map(|item| {
+            let id = attr(item, "id").unwrap_or_default();
+            let p = p_node();
+            p.children
+                .borrow_mut()
+                .push(text_node(&format!("[^{}]: ", footnote_label(&id))));
+            for child in item.children.borrow().iter() {
+                if is_element(child, "p") {
+                    p.children
+                        .borrow_mut()
+                        .extend(child.children.borrow().iter().cloned());
+                } else if !is_backlink(child) {
+                    p.children.borrow_mut().push(child.clone());
+                }
+            }
+            p
+        });
+        Some(definitions.collect())
+    })
+}
+
+/// WordPress replaces emoji with images like
+/// `<img class="wp-smiley" src=".../72x72/1f600.png">`. Turn them back
+/// into the characters named by the file.
+fn convert_emoji(body: &Node) -> bool {
+    replace_nodes(body, &mut |node| {
+        let is_emoji = is_element(node, "img")
+            && attr(node, "class").is_some_and(|class| {
+                class
+                    .split_whitespace()
+                    .any(|class| class == "wp-smiley" || class == "emoji")
+            });
+        if !is_emoji {
+            return None;
+        }
+        let emoji = attr(node, "src")
+            .and_then(|src| emoji_from_file_name(&src))
+            .or_else(|| attr(node, "alt"))?;
+        Some(vec![text_node(&emoji)])
+    })
+}
+
+/// Text dividers like `* * *` between the blocks of a post become
+/// `<hr>`, the same as separator blocks.
+fn convert_separators(body: &Node) -> bool {
+    static DIVIDER: LazyLock<Regex> =
+        LazyLock::new(|| Regex::new(r"^\s*(?:(?:[*\-_~=•·]\s*){3,}|⁂)\s*$").unwrap());
+
+    let mut changed = false;
+    for child in body.children.borrow_mut().iter_mut() {
+        let is_divider = match &child.data {
+            NodeData::Text { contents } => DIVIDER.is_match(&contents.borrow()),
+            _ => {
+                is_element(child, "p")
+                    && child_elements(child).is_empty()
+                    && DIVIDER.is_match(&text_content(child))
+            }
+        };
+        if is_divider {
+            *child = element_node("hr");
+            changed = true;
+        }
+    }
+    changed
+}
+
+/// `.../1f468-200d-1f469.png` is the sequence of those code points.
+fn emoji_from_file_name(src: &str) -> Option<String> {
+    let file_name = src.split(['?', '#']).next()?.rsplit('/').next()?;
+    let (stem, _) = file_name.rsplit_once('.')?;
+    stem.split('-')
+        .map(|hex| u32::from_str_radix(hex, 16).ok().and_then(char::from_u32))
+        .collect()
+}
+
+/// Zola makes its own ids for headings out of their text, so links
+/// within a post to WordPress heading ids are pointed to those instead.
+/// Links to anchors which won't exist after conversion are unwrapped.
+fn convert_anchors(body: &Node) -> bool {
+    let nodes = descendants(body);
+    let mut targets: HashMap<String, String> = HashMap::new();
+    let mut slugs: Vec<String> = Vec::new();
+    for heading in nodes.iter().filter(|node| is_heading(node)) {
+        // zola slugs headings with the `slug` crate, transliterating
+        // them to ascii, and numbers repeated ones: `a`, `a-1`, `a-2`
+        let base = slug::slugify(text_content(heading));
+        let mut slug = base.clone();
+        let mut n = 0;
+        while slugs.contains(&slug) {
+            n += 1;
+            slug = format!("{}-{}", base, n);
+        }
+        slugs.push(slug.clone());
+
+        let ids = std::iter::once(heading.clone())
+            .chain(descendants(heading))
+            .filter_map(|node| attr(&node, "id"));
+        for id in ids {
+            targets.insert(id, slug.clone());
+        }
+    }
+    for slug in &slugs {
+        targets.insert(slug.clone(), slug.clone());
+    }
+    // named anchors are kept as html, so links to them still work
+    for node in nodes.iter().filter(|node| is_element(node, "a")) {
+        if let Some(name) = attr(node, "name") {
+            targets.insert(name.clone(), name);
+        }
+    }
+
+    let mut rewritten = false;
+    let unwrapped = replace_nodes(body, &mut |node| {
+        if !is_element(node, "a") {
+            return None;
+        }
+        let href = attr(node, "href")?;
+        let id = href.strip_prefix('#')?;
+        match targets.get(id) {
+            Some(slug) if slug == id => None,
+            Some(slug) => {
+                set_attr(node, "href", &format!("#{}", slug));
+                rewritten = true;
+                None
+            }
+            None => {
+                warn!("Dropping link to missing anchor {}", href);
+                Some(node.children.borrow().clone())
+            }
+        }
+    });
+    rewritten || unwrapped
+}
+
+fn is_heading(node: &Node) -> bool {
+    ["h1", "h2", "h3", "h4", "h5", "h6"]
+        .iter()
+        .any(|tag| is_element(node, tag))
+}
+
+/// Link from a footnote definition back to its reference.
+fn is_backlink(node: &Node) -> bool {
+    is_element(node, "a") && attr(node, "href").is_some_and(|href| href.starts_with('#'))
+}
+
+/// `fn1` or `footnote-1` becomes `1`.
+fn footnote_label(id: &str) -> &str {
+    let label = id.trim_start_matches(|c: char| !c.is_ascii_digit());
+    if label.is_empty() {
+        id
+    } else {
+        label
+    }
+}
+
 fn find_child_element(parent: Rc<Node>, tag: &str) -> Rc<Node> {
     // Find the nth child
     let children = parent.children.borrow();
@@ -103,89 +886,434 @@ fn find_child_element(parent: Rc<Node>, tag: &str) -> Rc<Node> {
 
 #[cfg(test)]
 mod tests {
+    use crate::tag_handlers::html_to_markdown;
     use crate::transform_html::transform_html;
+    use crate::{Options, Shortcodes};
+    use html2md::parse_html;
 
     #[test]
     fn no_newlines_means_no_change() {
-        assert_eq!(transform_html("ab"), "ab");
-        assert_eq!(transform_html("<b>A</b>B<b>C</b>"), "<b>A</b>B<b>C</b>");
+        assert_eq!(transform_html("ab", &Options::default()), "ab");
+        assert_eq!(
+            transform_html("<b>A</b>B<b>C</b>", &Options::default()),
+            "<b>A</b>B<b>C</b>"
+        );
     }
 
     #[test]
     fn one_new_line_is_preserved() {
-        assert_eq!(transform_html("a\nb"), "a\nb");
-        assert_eq!(transform_html("a\n\nb\nc"), "a<p></p>b\nc");
+        assert_eq!(transform_html("a\nb", &Options::default()), "a\nb");
+        assert_eq!(
+            transform_html("a\n\nb\nc", &Options::default()),
+            "a<p></p>b\nc"
+        );
     }
 
     #[test]
     fn gaps_yield_separate_paragraphs() {
-        assert_eq!(transform_html("a\n\nb"), "a<p></p>b");
+        assert_eq!(transform_html("a\n\nb", &Options::default()), "a<p></p>b");
     }
 
     #[test]
     fn long_gaps_are_the_same_as_short_ones() {
-        assert_eq!(transform_html("a\n\n\n\n\n\nb"), "a<p></p>b");
+        assert_eq!(
+            transform_html("a\n\n\n\n\n\nb", &Options::default()),
+            "a<p></p>b"
+        );
     }
 
     #[test]
     fn leading_and_trailing_newlines_are_ignored() {
-        assert_eq!(transform_html("a\n\n"), "a<p></p>");
-        assert_eq!(transform_html("\n\na"), "\n\na");
-        assert_eq!(transform_html("a\n\nb\n\n"), "a<p></p>b<p></p>");
-        assert_eq!(transform_html("\n\na\n\nb\n\n"), "a<p></p>b<p></p>");
+        assert_eq!(transform_html("a\n\n", &Options::default()), "a<p></p>");
+        assert_eq!(transform_html("\n\na", &Options::default()), "\n\na");
+        assert_eq!(
+            transform_html("a\n\nb\n\n", &Options::default()),
+            "a<p></p>b<p></p>"
+        );
+        assert_eq!(
+            transform_html("\n\na\n\nb\n\n", &Options::default()),
+            "a<p></p>b<p></p>"
+        );
     }
 
     #[test]
     fn multiple_gaps_become_paras() {
-        assert_eq!(transform_html("a\n\nb\n\nc"), "a<p></p>b<p></p>c");
+        assert_eq!(
+            transform_html("a\n\nb\n\nc", &Options::default()),
+            "a<p></p>b<p></p>c"
+        );
     }
 
     #[test]
     fn tags_containing_gaps_are_preserved_as_is() {
-        assert_eq!(transform_html("<b>a\n\nb\n\nc</b>"), "<b>a\n\nb\n\nc</b>");
         assert_eq!(
-            transform_html("<b>a\n\nb\n\nc</b>\n\nd"),
+            transform_html("<b>a\n\nb\n\nc</b>", &Options::default()),
+            "<b>a\n\nb\n\nc</b>"
+        );
+        assert_eq!(
+            transform_html("<b>a\n\nb\n\nc</b>\n\nd", &Options::default()),
             "<b>a\n\nb\n\nc</b><p></p>d"
         );
         assert_eq!(
-            transform_html("a<b>b\n\nb\n\nb</b>\n\nc"),
+            transform_html("a<b>b\n\nb\n\nb</b>\n\nc", &Options::default()),
             "a<b>b\n\nb\n\nb</b><p></p>c"
         );
     }
 
     #[test]
     fn text_followed_by_tag_is_untouched() {
-        assert_eq!(transform_html("a\n\nb<tt>c</tt>"), "a<p></p>b<tt>c</tt>");
+        assert_eq!(
+            transform_html("a\n\nb<tt>c</tt>", &Options::default()),
+            "a<p></p>b<tt>c</tt>"
+        );
     }
 
     #[test]
     fn trailing_newline_after_tags_is_preserved() {
         assert_eq!(
-            transform_html("<tt>a</tt>\n\n<tt>b</tt>\n"),
+            transform_html("<tt>a</tt>\n\n<tt>b</tt>\n", &Options::default()),
             "<tt>a</tt><p></p><tt>b</tt>\n"
         );
     }
 
     #[test]
     fn comments_are_ok() {
-        assert_eq!(transform_html("a<!--  -->"), "a<!--  -->");
-        assert_eq!(transform_html("a\n\nb<!--  -->"), "a<p></p>b<!--  -->");
-        assert_eq!(transform_html("<!--  -->"), "<!--  -->");
-        assert_eq!(transform_html("<!-- a -->"), "<!-- a -->");
-        assert_eq!(transform_html("<p>a</p><!--  -->"), "<p>a</p><!--  -->");
-        assert_eq!(transform_html("<p>a<!--  -->b</p>"), "<p>a<!--  -->b</p>");
-        assert_eq!(transform_html("<p>a<!-- b -->c</p>"), "<p>a<!-- b -->c</p>");
+        assert_eq!(
+            transform_html("a<!--  -->", &Options::default()),
+            "a<!--  -->"
+        );
+        assert_eq!(
+            transform_html("a\n\nb<!--  -->", &Options::default()),
+            "a<p></p>b<!--  -->"
+        );
+        assert_eq!(
+            transform_html("<!--  -->", &Options::default()),
+            "<!--  -->"
+        );
+        assert_eq!(
+            transform_html("<!-- a -->", &Options::default()),
+            "<!-- a -->"
+        );
+        assert_eq!(
+            transform_html("<p>a</p><!--  -->", &Options::default()),
+            "<p>a</p><!--  -->"
+        );
+        assert_eq!(
+            transform_html("<p>a<!--  -->b</p>", &Options::default()),
+            "<p>a<!--  -->b</p>"
+        );
+        assert_eq!(
+            transform_html("<p>a<!-- b -->c</p>", &Options::default()),
+            "<p>a<!-- b -->c</p>"
+        );
     }
 
     #[test]
     fn leading_comments_are_skipped() {
         // For some reason, leading comments are moved out to the document level by html5ever.
         // This slightly incorrect, but hopefully unproblematic behaviour is documented here:
-        assert_eq!(transform_html("<!--  -->b\n\nc"), "b<p></p>c");
+        assert_eq!(
+            transform_html("<!--  -->b\n\nc", &Options::default()),
+            "b<p></p>c"
+        );
 
         // This only happens when we actually change the HTML, so when there are no bare text nodes
         // the text is unchanged.
-        assert_eq!(transform_html("<!--  -->b"), "<!--  -->b");
-        assert_eq!(transform_html("<!--  --><p>b</p>"), "<!--  --><p>b</p>");
+        assert_eq!(
+            transform_html("<!--  -->b", &Options::default()),
+            "<!--  -->b"
+        );
+        assert_eq!(
+            transform_html("<!--  --><p>b</p>", &Options::default()),
+            "<!--  --><p>b</p>"
+        );
+    }
+
+    #[test]
+    fn windows_line_endings_are_normalized() {
+        assert_eq!(
+            transform_html("a\r\n\r\nb", &Options::default()),
+            transform_html("a\n\nb", &Options::default())
+        );
+        assert_eq!(transform_html("a\r\nb", &Options::default()), "a\nb");
+    }
+
+    #[test]
+    fn audio_shortcodes_become_audio_tags() {
+        assert_eq!(
+            html_to_markdown(
+                &transform_html(
+                    r#"Episode 1:
+
+[audio mp3="https://example.com/ep1.mp3" ogg="https://example.com/ep1.ogg"][/audio]"#,
+                    &Options::default()
+                ),
+                &Options::default()
+            ),
+            "Episode 1:\n\n\
+             <audio controls=\"\"><source src=\"https://example.com/ep1.mp3\" type=\"audio/mpeg\">\
+             <source src=\"https://example.com/ep1.ogg\" type=\"audio/ogg\"></audio>"
+        );
+    }
+
+    #[test]
+    fn emoji_images_become_characters() {
+        let html = r#"<p>Hi <img src="https://s.w.org/images/core/emoji/14.0.0/72x72/1f600.png" alt="😀" class="wp-smiley" style="height: 1em;" /> <img src="/cat.png" alt="cat"></p>"#;
+        assert_eq!(
+            parse_html(&transform_html(html, &Options::default())),
+            "Hi 😀 ![cat](/cat.png)"
+        );
+    }
+
+    #[test]
+    fn anchor_links_point_to_zola_heading_ids() {
+        let html = r##"<h2 id="section-2">Getting Started</h2><p>See <a href="#section-2">above</a>, <a href="#getting-started">again</a> and <a href="#gone">gone</a>.</p>"##;
+        assert_eq!(
+            parse_html(&transform_html(html, &Options::default())),
+            "Getting Started\n----------\n\nSee [above](#getting-started), [again](#getting-started) and gone."
+        );
+    }
+
+    #[test]
+    fn anchor_links_to_non_ascii_headings_are_transliterated() {
+        let html =
+            r##"<h2 id="groesse">Größe &amp; Gewicht</h2><p>See <a href="#groesse">size</a>.</p>"##;
+        assert_eq!(
+            parse_html(&transform_html(html, &Options::default())),
+            "Größe & Gewicht\n----------\n\nSee [size](#grosse-gewicht)."
+        );
+    }
+
+    #[test]
+    fn footnotes_are_converted() {
+        let html = r##"<p>Text<sup><a href="#fn1" id="ref1">1</a></sup> more.</p>
+<ol class="footnotes"><li id="fn1">The note. <a href="#ref1">↩</a></li></ol>"##;
+        assert_eq!(
+            parse_html(&transform_html(html, &Options::default())),
+            "Text[^1] more.\n\n[^1]: The note."
+        );
+    }
+
+    #[test]
+    fn content_without_footnotes_is_unaffected() {
+        let html = r##"<p><a name="top"></a>See <a href="#top">top</a><sup>2</sup></p><ol><li id="x">a</li></ol>"##;
+        assert_eq!(transform_html(html, &Options::default()), html);
+    }
+
+    #[test]
+    fn embed_blocks_become_shortcodes() {
+        let html = r#"<!-- wp:embed {"url":"https:\/\/www.youtube.com\/watch?v=dQw4w9WgXcQ\u0026t=1","type":"video","providerNameSlug":"youtube"} -->
+<figure class="wp-block-embed is-provider-youtube"><div class="wp-block-embed__wrapper">
+https://www.youtube.com/watch?v=dQw4w9WgXcQ&amp;t=1
+</div></figure>
+<!-- /wp:embed -->
+
+<!-- wp:embed {"url":"https://example.com/talk","providerNameSlug":"example"} -->
+<figure class="wp-block-embed"><div class="wp-block-embed__wrapper">
+https://example.com/talk
+</div></figure>
+<!-- /wp:embed -->"#;
+        assert_eq!(
+            html_to_markdown(&transform_html(html, &Options::default()), &Options::default()),
+            "{{ youtube(id=\"dQw4w9WgXcQ\") }}\n\n[https://example.com/talk](https://example.com/talk)"
+        );
+    }
+
+    #[test]
+    fn literal_zola_syntax_is_escaped() {
+        let html = "<p>Write {{ foo_bar }} or {% baz %}, not {{/* qux */}}</p>";
+        assert_eq!(
+            html_to_markdown(
+                &transform_html(html, &Options::default()),
+                &Options::default()
+            ),
+            "Write {{/* foo_bar */}} or {%/* baz */%}, not {{/* qux */}}"
+        );
+    }
+
+    #[test]
+    fn zola_syntax_is_escaped_in_aligned_paragraphs() {
+        let html = r#"<p style="text-align: center">Use {{ name }} here</p>"#;
+        assert_eq!(
+            html_to_markdown(
+                &transform_html(html, &Options::default()),
+                &Options::default()
+            ),
+            "<p style=\"text-align: center\">Use {{/* name */}} here</p>"
+        );
+    }
+
+    #[test]
+    fn zola_syntax_is_escaped_in_kept_tags() {
+        let options = Options {
+            keep_tags: vec!["kbd".to_owned(), "details".to_owned()],
+            ..Options::default()
+        };
+        let html = "<p>Type <kbd>{{ x }}</kbd></p><details><p>{% y %}</p></details>";
+        assert_eq!(
+            html_to_markdown(&transform_html(html, &options), &options),
+            "Type <kbd>{{/* x */}}</kbd>\n\n<details><p>{%/* y */%}</p></details>"
+        );
+    }
+
+    #[test]
+    fn pullquotes_and_quotes_are_told_apart() {
+        let pullquote = r#"<!-- wp:pullquote -->
+<figure class="wp-block-pullquote"><blockquote><p>Less is more.</p><cite>Mies</cite></blockquote></figure>
+<!-- /wp:pullquote -->"#;
+        assert_eq!(
+            html_to_markdown(
+                &transform_html(pullquote, &Options::default()),
+                &Options::default()
+            ),
+            "{% pullquote(cite=\"Mies\") %}\n\nLess is more.\n\n{% end %}"
+        );
+
+        let quote = r#"<!-- wp:quote -->
+<blockquote class="wp-block-quote"><p>Less is more.</p><cite>Mies</cite></blockquote>
+<!-- /wp:quote -->"#;
+        assert_eq!(
+            html_to_markdown(
+                &transform_html(quote, &Options::default()),
+                &Options::default()
+            ),
+            "> Less is more.\n>\n> — Mies"
+        );
+    }
+
+    #[test]
+    fn layout_tables_are_unwrapped() {
+        let html = r#"<table border="0"><tr><td><img src="a.png"> Hello <b>there</b></td></tr></table>
+<table role="presentation"><tr><td><p>Left</p></td><td>Right</td></tr></table>"#;
+        assert_eq!(
+            html_to_markdown(
+                &transform_html(html, &Options::default()),
+                &Options::default()
+            ),
+            "![](a.png) Hello **there**\n\nLeft\n\nRight"
+        );
+    }
+
+    #[test]
+    fn data_tables_are_kept() {
+        let html = "<table><tr><th>Name</th></tr><tr><td>Zola</td></tr></table>\
+                    <table><tr><td>a</td><td>b</td></tr></table>";
+        assert_eq!(transform_html(html, &Options::default()), html);
+    }
+
+    #[test]
+    fn inline_latex_becomes_dollars() {
+        let html = r#"<p>Euler: [latex]e^{i\pi} + 1 = 0[/latex] and $latex a_1 &lt; b_1$, <math><semantics><mi>x</mi><annotation encoding="application/x-tex">x_*</annotation></semantics></math>.</p>"#;
+        assert_eq!(
+            html_to_markdown(
+                &transform_html(html, &Options::default()),
+                &Options::default()
+            ),
+            r"Euler: $e^{i\pi} + 1 = 0$ and $a_1 < b_1$, $x_*$."
+        );
+        assert_eq!(
+            html_to_markdown(
+                &transform_html("<p>[latex]x^{{2}}[/latex]</p>", &Options::default()),
+                &Options::default()
+            ),
+            "$x^{{/*2*/}}$"
+        );
+    }
+
+    #[test]
+    fn display_latex_gets_a_line_of_its_own() {
+        let html =
+            r#"<p>Sum:</p><p>[latex display="true"]\sum_{k=1}^n k = \frac{n(n+1)}{2}[/latex]</p>"#;
+        assert_eq!(
+            html_to_markdown(
+                &transform_html(html, &Options::default()),
+                &Options::default()
+            ),
+            "Sum:\n\n$$\\sum_{k=1}^n k = \\frac{n(n+1)}{2}$$"
+        );
+    }
+
+    #[test]
+    fn shortcodes_can_be_renamed_for_a_theme() {
+        let html = r#"<!-- wp:embed {"url":"https://youtu.be/dQw4w9WgXcQ","type":"video","providerNameSlug":"youtube"} /-->
+<figure class="wp-block-pullquote"><blockquote><p>Less is more.</p></blockquote></figure>"#;
+        let options = Options {
+            shortcodes: Shortcodes {
+                youtube: "yt".to_owned(),
+                pullquote: "quote".to_owned(),
+                ..Shortcodes::default()
+            },
+            ..Options::default()
+        };
+        assert_eq!(
+            html_to_markdown(&transform_html(html, &options), &options),
+            "{{ yt(id=\"dQw4w9WgXcQ\") }}\n\n{% quote() %}\n\nLess is more.\n\n{% end %}"
+        );
+    }
+
+    #[test]
+    fn whole_documents_are_unwrapped() {
+        let html = transform_html(
+            "<!DOCTYPE html>\n<html><head><title>Post</title></head>\
+             <body><p>Hello <b>world</b></p></body></html>",
+            &Options::default(),
+        );
+        assert_eq!(html, "<p>Hello <b>world</b></p>");
+        assert_eq!(
+            html_to_markdown(&html, &Options::default()),
+            "Hello **world**"
+        );
+    }
+
+    #[test]
+    fn separators_become_thematic_breaks() {
+        let options = Options::default();
+        let markdown = |html: &str| html_to_markdown(&transform_html(html, &options), &options);
+        assert_eq!(markdown("a<hr>b"), "a\n\n---\n\nb");
+        assert_eq!(
+            markdown(
+                "<p>a</p>\n<!-- wp:separator -->\n\
+                 <hr class=\"wp-block-separator has-alpha-channel-opacity\"/>\n\
+                 <!-- /wp:separator -->\n<p>b</p>"
+            ),
+            "a\n\n---\n\nb"
+        );
+        assert_eq!(markdown("<p>a</p><p>* * *</p><p>b</p>"), "a\n\n---\n\nb");
+        assert_eq!(markdown("a\n\n***\n\nb"), "a\n\n---\n\nb");
+        assert_eq!(markdown("<p>a **b** c</p>"), "a \\*\\*b\\*\\* c");
+        assert_eq!(markdown("<pre>***</pre>"), "```\n***\n```");
+    }
+
+    #[test]
+    fn code_shortcodes_become_fenced_code() {
+        let markdown = |html: &str| {
+            html_to_markdown(
+                &transform_html(html, &Options::default()),
+                &Options::default(),
+            )
+        };
+        assert_eq!(
+            markdown(
+                "<p>Loop:</p>\n[sourcecode language=\"python\"]\nfor i in range(3):\n\n    \
+                 print(i &lt; 3, \"&amp;\")\n[/sourcecode]\n<p>Done</p>"
+            ),
+            "Loop:\n\n```python\nfor i in range(3):\n\n    print(i < 3, \"&\")\n```\n\nDone"
+        );
+        assert_eq!(
+            markdown("[code lang=\"js\"]if (a < b && c) {}[/code]"),
+            "```js\nif (a < b && c) {}\n```"
+        );
+        assert_eq!(
+            markdown("[code title=\"x\"]```\n[/code]"),
+            "````\n```\n````"
+        );
+        assert_eq!(
+            markdown("[code]a = 1\n\n\n  \nb = 2[/code]"),
+            "```\na = 1\n\n\n  \nb = 2\n```"
+        );
+        assert_eq!(
+            markdown("[code lang=\"html\"]<p>{{ x }} {% if y %}{{/* z */}}</p>[/code]"),
+            "```html\n<p>{{/* x */}} {%/* if y */%}{{/* z */}}</p>\n```"
+        );
     }
 }
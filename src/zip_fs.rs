@@ -0,0 +1,185 @@
+//! Writing the converted site into a zip archive.
+
+use crate::{
+    config_contents, index_contents, links_contents, manifest_contents, page_contents,
+    section_contents, Config, Fs, IndexEntry, ManifestEntry, Page, Section,
+};
+use std::cell::RefCell;
+use std::collections::{HashMap, HashSet};
+use std::io::{self, Error, Read, Result, Seek, Write};
+use std::path::{Component, Path, PathBuf};
+use zip::write::FileOptions;
+use zip::ZipWriter;
+
+/// Puts generated files into a zip archive instead of the file system.
+/// The export is read from memory, other inputs like section templates
+/// from disk.
+pub struct ZipFs<W: Write + Seek> {
+    input: String,
+    /// Exports read from memory by their path, when there are several.
+    inputs: HashMap<PathBuf, String>,
+    zip: RefCell<ZipWriter<W>>,
+    /// Directory entries already in the archive.
+    dirs: RefCell<HashSet<String>>,
+}
+
+impl<W: Write + Seek> ZipFs<W> {
+    pub fn new(input: &str, writer: W) -> Self {
+        Self {
+            input: input.to_owned(),
+            inputs: HashMap::new(),
+            zip: RefCell::new(ZipWriter::new(writer)),
+            dirs: RefCell::new(HashSet::new()),
+        }
+    }
+
+    /// Make the export `contents` readable at `path`.
+    pub fn with_input(mut self, path: impl Into<PathBuf>, contents: &str) -> Self {
+        self.inputs.insert(path.into(), contents.to_owned());
+        self
+    }
+
+    /// Write the central directory and return the underlying writer.
+    pub fn finish(self) -> Result<W> {
+        self.zip.into_inner().finish().map_err(Error::other)
+    }
+
+    fn create_file(&self, path: &Path, contents: &mut impl Read) -> Result<()> {
+        let mut zip = self.zip.borrow_mut();
+        zip.start_file(entry_name(path), FileOptions::default())
+            .map_err(Error::other)?;
+        io::copy(contents, &mut *zip)?;
+        Ok(())
+    }
+}
+
+impl<W: Write + Seek> Fs for ZipFs<W> {
+    fn open(&self, path: &Path) -> Result<impl Read> {
+        Ok(self.inputs.get(path).unwrap_or(&self.input).as_bytes())
+    }
+
+    fn read_file(&self, path: &Path) -> Result<Option<String>> {
+        crate::RealFs {}.read_file(path)
+    }
+
+    fn create_dir_all<P>(&self, path: P) -> Result<()>
+    where
+        P: AsRef<Path>,
+    {
+        let name = entry_name(path.as_ref());
+        if name.is_empty() || !self.dirs.borrow_mut().insert(name.clone()) {
+            return Ok(());
+        }
+        self.zip
+            .borrow_mut()
+            .add_directory(name, FileOptions::default())
+            .map_err(Error::other)
+    }
+
+    fn create_page(&self, path: &Path, page: &Page) -> Result<()> {
+        self.create_file(path, &mut page_contents(page).as_bytes())
+    }
+
+    fn create_section(&self, path: &Path, section: &Section) -> Result<()> {
+        self.create_file(path, &mut section_contents(section).as_bytes())
+    }
+
+    fn fetch(&self, url: &str) -> Result<Vec<u8>> {
+        crate::RealFs {}.fetch(url)
+    }
+
+    fn read_bytes(&self, path: &Path) -> Result<Vec<u8>> {
+        crate::RealFs {}.read_bytes(path)
+    }
+
+    fn write_file(&self, path: &Path, contents: &[u8]) -> Result<()> {
+        self.create_file(path, &mut &*contents)
+    }
+
+    fn create_config(&self, path: &Path, config: &Config) -> Result<()> {
+        self.create_file(path, &mut config_contents(config).as_bytes())
+    }
+
+    fn create_links(&self, path: &Path, links: &[String]) -> Result<()> {
+        self.create_file(path, &mut links_contents(links).as_bytes())
+    }
+
+    fn create_manifest(&self, path: &Path, entries: &[ManifestEntry]) -> Result<()> {
+        self.create_file(path, &mut manifest_contents(entries).as_bytes())
+    }
+
+    fn create_index(&self, path: &Path, entries: &[IndexEntry]) -> Result<()> {
+        self.create_file(path, &mut index_contents(entries).as_bytes())
+    }
+
+    fn create_html(&self, path: &Path, html: &str) -> Result<()> {
+        self.create_file(path, &mut html.as_bytes())
+    }
+}
+
+/// Archive entries use `/` and are relative: `content/blog/post.md`.
+fn entry_name(path: &Path) -> String {
+    let parts: Vec<String> = path
+        .components()
+        .filter_map(|component| match component {
+            Component::Normal(part) => Some(part.to_string_lossy().into_owned()),
+            _ => None,
+        })
+        .collect();
+    parts.join("/")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ZipFs;
+    use crate::{convert, Options};
+    use std::io::Cursor;
+    use zip::ZipArchive;
+
+    #[test]
+    fn files_become_zip_entries() {
+        // Given a WP export with a post in it
+        let input = r#"<?xml version="1.0" encoding="UTF-8" ?>
+            <rss version="2.0"
+                xmlns:content="http://purl.org/rss/1.0/modules/content/"
+                xmlns:wp="http://wordpress.org/export/1.2/"
+            >
+            <channel>
+                <title>Blog</title>
+                <wp:base_site_url>https://example.com</wp:base_site_url>
+                <item>
+                    <title>Post 1</title>
+                    <pubDate>Mon, 01 Sep 2008 21:02:27 +0000</pubDate>
+                    <link>https://example.com/blog/post1</link>
+                    <content:encoded><![CDATA[Hello]]></content:encoded>
+                    <wp:post_type><![CDATA[post]]></wp:post_type>
+                    <wp:status><![CDATA[publish]]></wp:status>
+                </item>
+            </channel>
+        </rss>
+        "#;
+
+        // When we convert it into an in-memory zip
+        let fs = ZipFs::new(input, Cursor::new(Vec::new()));
+        let options = Options {
+            config: true,
+            ..Options::default()
+        };
+        convert("".into(), "site/content".into(), &options, &fs).unwrap();
+        let archive = ZipArchive::new(fs.finish().unwrap()).unwrap();
+
+        // Then the archive has an entry per generated file
+        let mut names: Vec<&str> = archive.file_names().collect();
+        names.sort_unstable();
+        assert_eq!(
+            names,
+            &[
+                "site/config.toml",
+                "site/content/_index.md",
+                "site/content/blog/",
+                "site/content/blog/_index.md",
+                "site/content/blog/post1.md",
+            ]
+        );
+    }
+}